@@ -1,5 +1,5 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion, BenchmarkId};
-use nsv::{encode, decode, decode_bytes, decode_bytes_projected, Reader};
+use nsv::{encode, decode, decode_bytes, decode_bytes_interned, Interner, decode_bytes_filtered, decode_bytes_projected, decode_columns, ColumnSelector, IncrementalDocument, Reader, Table};
 
 fn generate_test_data(rows: usize, cells_per_row: usize) -> Vec<Vec<String>> {
     (0..rows)
@@ -70,6 +70,14 @@ fn bench_encode(c: &mut Criterion) {
     });
 }
 
+fn bench_encode_large(c: &mut Criterion) {
+    let data = generate_test_data(100_000, 10);
+
+    c.bench_function("dumps_100k_rows", |b| {
+        b.iter(|| encode(black_box(&data)))
+    });
+}
+
 // ── Projection benchmarks ────────────────────────────────────────────
 
 fn bench_projection_10k(c: &mut Criterion) {
@@ -124,6 +132,105 @@ fn bench_projection_100k(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_filtered_100k(c: &mut Criterion) {
+    let data = generate_test_data(100_000, 10);
+    let nsv_str = encode(&data);
+    let nsv_bytes = nsv_str.as_bytes();
+
+    // Keeps 1% of rows
+    let keep = |cell: &[u8]| cell.ends_with(b"00_col1");
+
+    let mut group = c.benchmark_group("filtered_100k_x_10");
+
+    group.bench_function("projected_then_filter", |b| {
+        b.iter(|| {
+            decode_bytes_projected(black_box(nsv_bytes), &[0, 5, 1])
+                .into_iter()
+                .filter(|row| keep(&row[2]))
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.bench_function("filtered", |b| {
+        b.iter(|| decode_bytes_filtered(black_box(nsv_bytes), &[0, 5], &[1], |keys| keep(&keys[0])))
+    });
+
+    group.finish();
+}
+
+fn bench_columns_100k(c: &mut Criterion) {
+    let data = generate_test_data(100_000, 10);
+    let nsv_str = encode(&data);
+    let nsv_bytes = nsv_str.as_bytes();
+    let columns = [0, 3, 7];
+
+    let mut group = c.benchmark_group("columns_100k_x_10");
+
+    group.bench_function("projected_then_transpose", |b| {
+        b.iter(|| {
+            let rows = decode_bytes_projected(black_box(nsv_bytes), &columns);
+            let mut out: Vec<Vec<_>> = columns.iter().map(|_| Vec::with_capacity(rows.len())).collect();
+            for row in rows {
+                for (column, cell) in out.iter_mut().zip(row) {
+                    column.push(cell);
+                }
+            }
+            out
+        })
+    });
+
+    let selector = ColumnSelector::from(&columns[..]);
+    group.bench_function("decode_columns", |b| {
+        b.iter(|| decode_columns(black_box(nsv_bytes), &selector))
+    });
+
+    group.finish();
+}
+
+fn bench_table_100k(c: &mut Criterion) {
+    let data = generate_test_data(100_000, 10);
+    let nsv_str = encode(&data);
+    let nsv_bytes = nsv_str.as_bytes();
+
+    let mut group = c.benchmark_group("owned_100k_x_10");
+
+    group.bench_function("decode_bytes_into_owned", |b| {
+        b.iter(|| {
+            decode_bytes(black_box(nsv_bytes))
+                .into_iter()
+                .map(|row| row.into_iter().map(|cell| cell.into_owned()).collect::<Vec<_>>())
+                .collect::<Vec<_>>()
+        })
+    });
+
+    group.bench_function("table", |b| {
+        b.iter(|| Table::decode(black_box(nsv_bytes)))
+    });
+
+    group.finish();
+}
+
+fn bench_interned_100k(c: &mut Criterion) {
+    // Low-cardinality columns: country, status, currency
+    let (countries, statuses, currencies) = (["FR", "DE", "IT", "ES"], ["open", "closed"], ["EUR", "USD", "GBP"]);
+    let data: Vec<Vec<String>> = (0..100_000)
+        .map(|i| vec![countries[i % 4].to_string(), statuses[i % 2].to_string(), currencies[i % 3].to_string()])
+        .collect();
+    let nsv_str = encode(&data);
+
+    let mut group = c.benchmark_group("interned_100k_x_3");
+
+    group.bench_function("decode_strings", |b| {
+        b.iter(|| decode(black_box(&nsv_str)))
+    });
+
+    group.bench_function("interned", |b| {
+        b.iter(|| decode_bytes_interned(black_box(nsv_str.as_bytes()), &mut Interner::new()))
+    });
+
+    group.finish();
+}
+
 fn bench_projection_wide(c: &mut Criterion) {
     // Wide table: 1000 rows x 100 columns — projection shines here
     let data = generate_test_data(1_000, 100);
@@ -151,6 +258,29 @@ fn bench_projection_wide(c: &mut Criterion) {
     group.finish();
 }
 
+fn bench_incremental_edit_100k(c: &mut Criterion) {
+    let data = generate_test_data(100_000, 10);
+    let nsv_bytes = encode(&data).into_bytes();
+
+    let mut group = c.benchmark_group("edit_100k_x_10");
+
+    group.bench_function("decode_bytes", |b| {
+        b.iter(|| decode_bytes(black_box(&nsv_bytes)))
+    });
+
+    // Type a character into a cell mid-document, then delete it
+    let mut doc = IncrementalDocument::new(nsv_bytes.clone());
+    let at = doc.row_range(50_000).unwrap().start + 3;
+    group.bench_function("incremental_edit", |b| {
+        b.iter(|| {
+            doc.edit(at..at, black_box(b"x"));
+            doc.edit(at..at + 1, b"")
+        })
+    });
+
+    group.finish();
+}
+
 // ── Reader (streaming) benchmarks ────────────────────────────────────
 
 fn bench_reader_10k(c: &mut Criterion) {
@@ -191,8 +321,14 @@ criterion_group!(
     bench_loads_xlarge,
     bench_loads_various_sizes,
     bench_encode,
+    bench_encode_large,
     bench_projection_10k,
     bench_projection_100k,
+    bench_filtered_100k,
+    bench_columns_100k,
+    bench_table_100k,
+    bench_interned_100k,
+    bench_incremental_edit_100k,
     bench_projection_wide,
     bench_reader_10k,
     bench_reader_100k,
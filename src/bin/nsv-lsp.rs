@@ -0,0 +1,15 @@
+//! NSV language server over stdio. See `nsv::lsp`.
+
+use std::io;
+use std::process::ExitCode;
+
+fn main() -> ExitCode {
+    match nsv::lsp::run(io::stdin().lock(), io::stdout().lock()) {
+        Ok(true) => ExitCode::SUCCESS,
+        Ok(false) => ExitCode::FAILURE,
+        Err(e) => {
+            eprintln!("nsv-lsp: {}", e);
+            ExitCode::FAILURE
+        }
+    }
+}
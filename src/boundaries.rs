@@ -0,0 +1,205 @@
+//! Row-boundary discovery, for splitting an input into independently
+//! decodable pieces (across threads, processes or machines).
+//!
+//! A *row start* is an offset where a row begins: `0`, and the offset right
+//! after every row terminator. A row terminator is an LF at a line start,
+//! that is an LF at offset `0` or directly preceded by another LF. Since a
+//! literal LF is never part of a cell's content, this is decidable from the
+//! bytes around the offset alone.
+//!
+//! Runs of consecutive LFs resolve as follows. When a run follows cell content
+//! (`x\n\n\n\n`), its first LF ends that cell, the second ends the row, and
+//! every further LF ends one empty row. When a run begins at a row start
+//! (at offset `0`, or right after a row terminator), every LF in it ends an
+//! empty row. Either way, a row starts right after each LF of the run except
+//! the one ending a cell:
+//!
+//! ```text
+//! a \n \n \n \n b \n \n
+//! 0 1  2  3  4  5 6  7      row starts: 0, 3, 4, 5, 8
+//! ```
+//!
+//! Cutting an input at any row start gives two pieces whose decodes,
+//! concatenated, equal the decode of the whole.
+
+use memchr::memmem;
+
+/// The first row start at or after `offset`, or `input.len()` if there is none.
+///
+/// Offsets past the end are clamped to `input.len()`. Costs one forward scan
+/// for the next LF pair, O(distance to it).
+///
+/// ```
+/// use nsv::boundaries::next_row_start;
+///
+/// let input = b"a\n\n\n\nb\n\n";
+/// assert_eq!(next_row_start(input, 1), 3);
+/// assert_eq!(next_row_start(input, 4), 4);
+/// assert_eq!(next_row_start(input, 6), 8);
+/// ```
+pub fn next_row_start(input: &[u8], offset: usize) -> usize {
+    if offset == 0 || offset >= input.len() {
+        return offset.min(input.len());
+    }
+    if offset == 1 && input[0] == b'\n' {
+        return 1;
+    }
+    // Past the first byte, a row starts right after an LF pair.
+    let from = offset.saturating_sub(2);
+    memmem::find(&input[from..], b"\n\n").map_or(input.len(), |at| from + at + 2)
+}
+
+/// The start of row `n` (counting from 0), or `input.len()` if the input has
+/// `n` rows or fewer.
+///
+/// Only looks for row ends, one LF-pair search per skipped row.
+///
+/// ```
+/// use nsv::boundaries::nth_row_start;
+///
+/// let input = b"a\n\n\nb\nc\n\n";
+/// assert_eq!(nth_row_start(input, 1), 3);
+/// assert_eq!(nth_row_start(input, 2), 4);
+/// assert_eq!(nth_row_start(input, 3), input.len());
+/// ```
+pub fn nth_row_start(input: &[u8], n: usize) -> usize {
+    let pair = memmem::Finder::new(b"\n\n");
+    let mut pos = 0;
+    for _ in 0..n {
+        match input.get(pos) {
+            None => break,
+            // An empty row: its terminator is right at its start.
+            Some(b'\n') => pos += 1,
+            Some(_) => match pair.find(&input[pos..]) {
+                Some(at) => pos += at + 2,
+                None => return input.len(),
+            },
+        }
+    }
+    pos
+}
+
+/// Split `input` into at most `n` pieces of roughly equal size, cut at row
+/// starts.
+///
+/// Returns the sorted, distinct cut points, beginning with `0` and ending with
+/// `input.len()`; consecutive points delimit a piece (so an empty input gives
+/// `[0]`, no pieces). Each inner point is the first row start at or after an
+/// evenly spaced offset `i * (input.len() / n)`, so pieces can come out fewer
+/// than `n`, or uneven, when rows are long. `n == 0` is treated as `1`.
+///
+/// ```
+/// use nsv::boundaries::split_points;
+///
+/// let input = b"a\n\nb\n\nc\n\nd\n\n";
+/// let points = split_points(input, 2);
+/// assert_eq!(points, [0, 6, 12]);
+///
+/// let rows: Vec<_> = points.windows(2).flat_map(|w| nsv::decode_bytes(&input[w[0]..w[1]])).collect();
+/// assert_eq!(rows, nsv::decode_bytes(input));
+/// ```
+pub fn split_points(input: &[u8], n: usize) -> Vec<usize> {
+    let n = n.max(1);
+    let chunk_size = input.len() / n;
+    let mut points = Vec::with_capacity(n + 1);
+    points.push(0);
+    if chunk_size > 0 {
+        points.extend((1..n).map(|i| next_row_start(input, i * chunk_size)));
+    }
+    points.push(input.len());
+    points.dedup();
+    points
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::random_input;
+
+    /// Row starts by definition: 0, and after every LF at a line start.
+    fn row_starts_reference(input: &[u8]) -> Vec<usize> {
+        let mut starts = vec![0];
+        let mut line_start = 0;
+        for (i, &b) in input.iter().enumerate() {
+            if b == b'\n' {
+                if i == line_start {
+                    starts.push(i + 1);
+                }
+                line_start = i + 1;
+            }
+        }
+        starts
+    }
+
+    #[test]
+    fn test_run_resolution() {
+        let input = b"a\n\n\n\nb\n\n";
+        let starts: Vec<usize> = (0..=input.len()).map(|offset| next_row_start(input, offset)).collect();
+        assert_eq!(starts, [0, 3, 3, 3, 4, 5, 8, 8, 8]);
+        assert_eq!(row_starts_reference(input), [0, 3, 4, 5, 8]);
+        assert_eq!(next_row_start(b"\n\n\n", 1), 1);
+        assert_eq!(next_row_start(b"a\n", 7), 2);
+        assert_eq!(next_row_start(b"", 0), 0);
+    }
+
+    #[test]
+    fn test_next_row_start_matches_definition() {
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 7) % 120);
+            let starts = row_starts_reference(&input);
+            for offset in 0..=input.len() + 1 {
+                let expected = starts.iter().copied().find(|&p| p >= offset).unwrap_or(input.len());
+                assert_eq!(next_row_start(&input, offset), expected, "seed {} offset {}", seed, offset);
+            }
+        }
+    }
+
+    #[test]
+    fn test_any_split_decodes_correctly() {
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 13) % 120);
+            let whole = crate::decode_bytes(&input);
+            for offset in 0..=input.len() {
+                let p = next_row_start(&input, offset);
+                let mut rows = crate::decode_bytes(&input[..p]);
+                rows.extend(crate::decode_bytes(&input[p..]));
+                assert_eq!(rows, whole, "seed {} split {}", seed, p);
+            }
+        }
+    }
+
+    #[test]
+    fn test_nth_row_start_matches_definition() {
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 7) % 120);
+            let starts = row_starts_reference(&input);
+            for n in 0..starts.len() + 2 {
+                let expected = starts.get(n).copied().unwrap_or(input.len());
+                assert_eq!(nth_row_start(&input, n), expected, "seed {} n {}", seed, n);
+            }
+        }
+    }
+
+    #[test]
+    fn test_split_points() {
+        for seed in 0..100 {
+            let input = random_input(seed, (seed as usize * 37) % 2_000);
+            let whole = crate::decode_bytes(&input);
+            let starts = row_starts_reference(&input);
+            for n in [0, 1, 2, 3, 8, 64, 5_000] {
+                let points = split_points(&input, n);
+                assert_eq!(points.first(), Some(&0));
+                assert_eq!(points.last(), Some(&input.len()));
+                assert!(points.len() <= n.max(1) + 1);
+                assert!(points.windows(2).all(|w| w[0] < w[1]));
+                for &p in &points[..points.len() - 1] {
+                    assert!(starts.contains(&p), "seed {} n {} point {}", seed, n, p);
+                }
+
+                let rows: Vec<_> = points.windows(2).flat_map(|w| crate::decode_bytes(&input[w[0]..w[1]])).collect();
+                assert_eq!(rows, whole, "seed {} n {}", seed, n);
+            }
+        }
+        assert_eq!(split_points(b"", 4), [0]);
+    }
+}
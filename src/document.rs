@@ -0,0 +1,275 @@
+//! Documents that stay decoded while they are edited.
+//!
+//! An [`IncrementalDocument`] keeps its text, its decoded rows and the offset
+//! where each row starts. Whether an offset starts a row depends only on the
+//! two bytes before it (see [`crate::boundaries`]), so an edit can only make or
+//! unmake row starts up to two bytes past its end. [`IncrementalDocument::edit`]
+//! re-decodes from the start of the row holding the edit to the first row
+//! start at least two bytes after it, and keeps every other row as it was.
+
+use std::borrow::Cow;
+use std::ops::Range;
+
+use crate::{decode_rows, AllColumns};
+
+/// NSV text kept decoded across edits, for editors.
+///
+/// Rows are those of [`crate::decode_bytes`] over the current text, owned.
+/// Each row also has a byte range in the text: from its start up to the start
+/// of the next row, terminator included. Ranges tile the whole text.
+///
+/// ```
+/// use nsv::IncrementalDocument;
+///
+/// let mut doc = IncrementalDocument::new(b"a\nb\n\nc\n\n".to_vec());
+/// assert_eq!(doc.row_range(1), Some(5..8));
+///
+/// let change = doc.edit(5..6, b"c\\n");
+/// assert_eq!(change.inserted, 1..2);
+/// assert_eq!(doc.row(1), Some(&[b"c\n".to_vec()][..]));
+///
+/// // Removing a row's empty line merges it with the next
+/// let change = doc.edit(4..5, b"");
+/// assert_eq!((change.removed, change.inserted), (0..2, 0..1));
+/// assert_eq!(doc.rows(), [vec![b"a".to_vec(), b"b".to_vec(), b"c\n".to_vec()]]);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct IncrementalDocument {
+    text: Vec<u8>,
+    rows: Vec<Vec<Vec<u8>>>,
+    /// Offset of each row in `text`.
+    starts: Vec<usize>,
+}
+
+/// The rows changed by an [`IncrementalDocument::edit`].
+///
+/// Rows `removed` of the document before the edit were replaced by rows
+/// `inserted` after it. Both ranges begin at the same row; rows before them are
+/// untouched, and rows after them have the same cells as before, moved by
+/// `inserted.len() - removed.len()` rows. Both ranges are empty when the edit
+/// left every row's cells as they were.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RowEdit {
+    pub removed: Range<usize>,
+    pub inserted: Range<usize>,
+}
+
+impl IncrementalDocument {
+    /// Decode `text`, in parallel above the same threshold as
+    /// [`crate::decode_bytes`].
+    pub fn new(text: impl Into<Vec<u8>>) -> Self {
+        let text = text.into();
+        let rows = crate::decode_bytes(&text).into_iter().map(owned_row).collect();
+        let starts = row_starts(&text, 0);
+        IncrementalDocument { text, rows, starts }
+    }
+
+    /// The current text.
+    pub fn text(&self) -> &[u8] {
+        &self.text
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// Every row, as [`crate::decode_bytes`] would give it.
+    pub fn rows(&self) -> &[Vec<Vec<u8>>] {
+        &self.rows
+    }
+
+    /// The cells of row `index`.
+    pub fn row(&self, index: usize) -> Option<&[Vec<u8>]> {
+        self.rows.get(index).map(Vec::as_slice)
+    }
+
+    /// The bytes of row `index` in the text, its terminator included.
+    pub fn row_range(&self, index: usize) -> Option<Range<usize>> {
+        let start = *self.starts.get(index)?;
+        Some(start..self.starts.get(index + 1).copied().unwrap_or(self.text.len()))
+    }
+
+    /// The row whose range holds byte `offset`, or `None` past the end of the
+    /// text.
+    pub fn row_at(&self, offset: usize) -> Option<usize> {
+        if offset >= self.text.len() {
+            return None;
+        }
+        Some(self.starts.partition_point(|&start| start <= offset) - 1)
+    }
+
+    /// Replace the bytes in `range` with `replacement`, and re-decode the rows
+    /// it touches.
+    ///
+    /// Costs a decode of the touched rows, plus moving the text, rows and row
+    /// offsets after them.
+    ///
+    /// # Panics
+    ///
+    /// If `range` is decreasing or ends past the end of the text.
+    pub fn edit(&mut self, range: Range<usize>, replacement: &[u8]) -> RowEdit {
+        assert!(range.start <= range.end && range.end <= self.text.len(), "edit range {:?} out of bounds", range);
+
+        // The row holding the edit's start begins no later than it, so it still
+        // starts there; the first row start two bytes past the edit's end keeps
+        // the bytes that make it one, so it still starts a row too.
+        let first = self.starts.partition_point(|&start| start <= range.start).saturating_sub(1);
+        let last = self.starts.partition_point(|&start| start < range.end + 2);
+        let from = self.starts.get(first).copied().unwrap_or(0);
+        let to = self.starts.get(last).copied().unwrap_or(self.text.len()) - range.len() + replacement.len();
+
+        self.text.splice(range.clone(), replacement.iter().copied());
+        let piece = &self.text[from..to];
+        let rows: Vec<Vec<Vec<u8>>> = decode_rows(&AllColumns, piece).into_iter().map(owned_row).collect();
+        let starts = row_starts(piece, from);
+        debug_assert_eq!(rows.len(), starts.len());
+
+        for start in &mut self.starts[last..] {
+            *start = *start - range.len() + replacement.len();
+        }
+        let inserted = rows.len();
+        self.starts.splice(first..last, starts);
+        let removed: Vec<Vec<Vec<u8>>> = self.rows.splice(first..last, rows).collect();
+
+        // Leave out the rows that came back the same
+        let new = &self.rows[first..first + inserted];
+        let same_front = removed.iter().zip(new).take_while(|(old, new)| old == new).count();
+        let same_back = removed[same_front..].iter().rev().zip(new[same_front..].iter().rev()).take_while(|(old, new)| old == new).count();
+        RowEdit {
+            removed: first + same_front..first + removed.len() - same_back,
+            inserted: first + same_front..first + inserted - same_back,
+        }
+    }
+}
+
+impl From<Vec<u8>> for IncrementalDocument {
+    fn from(text: Vec<u8>) -> Self {
+        IncrementalDocument::new(text)
+    }
+}
+
+fn owned_row(row: Vec<Cow<'_, [u8]>>) -> Vec<Vec<u8>> {
+    row.into_iter().map(Cow::into_owned).collect()
+}
+
+/// The start of every row of `piece`, which begins at a row start, moved by
+/// `offset`.
+fn row_starts(piece: &[u8], offset: usize) -> Vec<usize> {
+    let mut starts = Vec::new();
+    if !piece.is_empty() {
+        starts.push(offset);
+    }
+    // A row ends at every LF at a line start
+    for at in memchr::memchr_iter(b'\n', piece) {
+        if (at == 0 || piece[at - 1] == b'\n') && at + 1 < piece.len() {
+            starts.push(offset + at + 1);
+        }
+    }
+    starts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn check_consistent(doc: &IncrementalDocument) {
+        let text = doc.text();
+        let decoded: Vec<Vec<Vec<u8>>> = crate::decode_bytes(text).into_iter().map(owned_row).collect();
+        assert_eq!(doc.rows, decoded, "{:?}", String::from_utf8_lossy(text));
+        // Each row's range decodes to that row alone
+        assert_eq!(doc.starts.len(), doc.len());
+        for (i, row) in doc.rows.iter().enumerate() {
+            let range = doc.row_range(i).unwrap();
+            assert_eq!(crate::boundaries::next_row_start(text, range.start), range.start);
+            let piece: Vec<Vec<Vec<u8>>> = crate::decode_bytes(&text[range]).into_iter().map(owned_row).collect();
+            assert_eq!(piece, std::slice::from_ref(row), "row {} of {:?}", i, String::from_utf8_lossy(text));
+        }
+    }
+
+    /// Apply `edit` to `rows` as a splice.
+    fn replay(rows: &[Vec<Vec<u8>>], edit: &RowEdit, doc: &IncrementalDocument) -> Vec<Vec<Vec<u8>>> {
+        let mut replayed = rows.to_vec();
+        replayed.splice(edit.removed.clone(), doc.rows[edit.inserted.clone()].iter().cloned());
+        replayed
+    }
+
+    #[test]
+    fn test_every_small_edit() {
+        let mut texts = vec![Vec::new()];
+        let mut last: Vec<Vec<u8>> = vec![Vec::new()];
+        for _ in 0..4 {
+            last = last.iter().flat_map(|prefix| b"\n\\nx".iter().map(move |&b| [&prefix[..], &[b]].concat())).collect();
+            texts.extend(last.iter().cloned());
+        }
+        let replacements: [&[u8]; 6] = [b"", b"\n", b"\\", b"x", b"\n\n", b"y\\\n"];
+
+        for text in &texts {
+            let doc = IncrementalDocument::new(text.clone());
+            check_consistent(&doc);
+            for start in 0..=text.len() {
+                for end in start..=text.len() {
+                    for replacement in replacements {
+                        let mut edited = doc.clone();
+                        let edit = edited.edit(start..end, replacement);
+                        check_consistent(&edited);
+                        assert_eq!(replay(&doc.rows, &edit, &edited), edited.rows);
+                        assert_eq!(edit.removed.start, edit.inserted.start);
+                    }
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_rows_and_ranges() {
+        let mut doc = IncrementalDocument::new(b"\na\\\\\n\\\n\n\n\nb".to_vec());
+        assert_eq!(doc.len(), 5);
+        let ranges: Vec<_> = (0..doc.len()).map(|i| doc.row_range(i).unwrap()).collect();
+        assert_eq!(ranges, [0..1, 1..8, 8..9, 9..10, 10..11]);
+        assert_eq!(doc.row_range(5), None);
+        assert_eq!([0, 1, 7, 8, 10].map(|offset| doc.row_at(offset)), [Some(0), Some(1), Some(1), Some(2), Some(4)]);
+        assert_eq!(doc.row_at(11), None);
+
+        // Editing inside a cell changes only its row
+        let edit = doc.edit(2..4, b"\\n");
+        assert_eq!(edit, RowEdit { removed: 1..2, inserted: 1..2 });
+        assert_eq!(doc.row(1), Some(&[b"a\n".to_vec(), Vec::new()][..]));
+
+        // An edit that decodes the same changes nothing
+        let edit = doc.edit(2..3, b"\\");
+        assert!(edit.removed.is_empty() && edit.inserted.is_empty());
+
+        // Splitting a row in two
+        let edit = doc.edit(5..5, b"\n");
+        assert_eq!(edit, RowEdit { removed: 1..2, inserted: 1..3 });
+        check_consistent(&doc);
+
+        let mut empty = IncrementalDocument::default();
+        assert!(empty.is_empty());
+        assert_eq!(empty.edit(0..0, b"x"), RowEdit { removed: 0..0, inserted: 0..1 });
+        assert_eq!(empty.rows(), [vec![b"x".to_vec()]]);
+    }
+
+    #[test]
+    fn test_edits_touch_few_rows() {
+        let data: Vec<Vec<String>> = (0..20_000).map(|i| vec![format!("{}", i), format!("v\\{}", i % 5)]).collect();
+        let mut doc = IncrementalDocument::new(crate::encode(&data).into_bytes());
+        assert_eq!(doc.len(), data.len());
+
+        let at = doc.row_range(1234).unwrap().start;
+        assert_eq!(doc.edit(at..at + 4, b"x"), RowEdit { removed: 1234..1235, inserted: 1234..1235 });
+        assert_eq!(doc.row(1234).unwrap()[0], b"x");
+
+        // Deleting a row terminator joins two rows
+        let end = doc.row_range(500).unwrap().end;
+        assert_eq!(doc.edit(end - 1..end, b""), RowEdit { removed: 500..502, inserted: 500..501 });
+        assert_eq!(doc.row(500).unwrap().len(), 4);
+        assert_eq!(doc.len(), data.len() - 1);
+        check_consistent(&doc);
+    }
+}
@@ -0,0 +1,325 @@
+//! Cell interning, for columns with few distinct values repeated many times.
+//!
+//! An [`Interner`] keeps one shared copy of each distinct cell value and hands
+//! out [`Symbol`]s for them. [`decode_bytes_interned`] and
+//! [`decode_bytes_projected_interned`] decode straight into symbols: each chunk
+//! of rows builds its own dictionary, borrowing from the input where it can, and
+//! the dictionaries are merged into the caller's interner in input order. Values
+//! get symbols in order of first appearance, sequential or parallel.
+
+use std::borrow::Cow;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{cell, scan_lines, Columns, DecodeOptions};
+
+/// Handle to a value in an [`Interner`]. Cheap to copy, compare and hash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Symbol(u32);
+
+impl Symbol {
+    /// Position of the value in its interner, counting from 0 in order of
+    /// interning.
+    pub fn index(self) -> usize {
+        self.0 as usize
+    }
+}
+
+/// Distinct cell values, each stored once.
+///
+/// Symbols are only meaningful for the interner that issued them. Keep one
+/// interner across decodes to share values between inputs.
+///
+/// ```
+/// use nsv::{decode_bytes_interned, Interner};
+///
+/// let mut interner = Interner::new();
+/// let rows = decode_bytes_interned(b"FR\nEUR\n\nDE\nEUR\n\n", &mut interner);
+/// assert_eq!(rows[0][1], rows[1][1]);
+/// assert_eq!(interner.len(), 3);
+/// assert_eq!(interner.resolve(rows[1][0]), b"DE");
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct Interner {
+    symbols: HashMap<Arc<[u8]>, Symbol>,
+    values: Vec<Arc<[u8]>>,
+}
+
+impl Interner {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The symbol for `value`, adding it if it's new.
+    ///
+    /// # Panics
+    ///
+    /// If the interner already holds `u32::MAX` values.
+    pub fn intern(&mut self, value: &[u8]) -> Symbol {
+        if let Some(&symbol) = self.symbols.get(value) {
+            return symbol;
+        }
+        let symbol = Symbol(u32::try_from(self.values.len()).expect("too many interned values"));
+        let value: Arc<[u8]> = value.into();
+        self.values.push(Arc::clone(&value));
+        self.symbols.insert(value, symbol);
+        symbol
+    }
+
+    /// The symbol for `value`, if it was interned.
+    pub fn get(&self, value: &[u8]) -> Option<Symbol> {
+        self.symbols.get(value).copied()
+    }
+
+    /// The value of `symbol`.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` was not issued by this interner.
+    pub fn resolve(&self, symbol: Symbol) -> &[u8] {
+        &self.values[symbol.index()]
+    }
+
+    /// The value of `symbol`, as a shared handle that outlives the interner.
+    ///
+    /// # Panics
+    ///
+    /// If `symbol` was not issued by this interner.
+    pub fn resolve_shared(&self, symbol: Symbol) -> Arc<[u8]> {
+        Arc::clone(&self.values[symbol.index()])
+    }
+
+    /// Number of distinct values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Every symbol with its value, in order of interning.
+    pub fn iter(&self) -> impl ExactSizeIterator<Item = (Symbol, &[u8])> + '_ {
+        self.values.iter().enumerate().map(|(i, value)| (Symbol(i as u32), &value[..]))
+    }
+}
+
+/// Decode `input` into symbols from `interner`, which gains the values it
+/// hasn't seen. Same rows and cells as [`crate::decode_bytes`], and parallel
+/// above the same threshold.
+pub fn decode_bytes_interned(input: &[u8], interner: &mut Interner) -> Vec<Vec<Symbol>> {
+    decode_interned(input, None, interner)
+}
+
+/// Decode only `columns` of `input` into symbols from `interner`: the
+/// low-cardinality columns of a table, say. Rows are as in
+/// [`crate::decode_bytes_projected`], with missing cells interned as empty;
+/// `columns` can be a [`crate::ColumnSelector`] too.
+///
+/// ```
+/// use nsv::{decode_bytes_projected_interned, Interner};
+///
+/// let input = b"1\nFR\nEUR\n\n2\nDE\nEUR\n\n3\nFR\n\n";
+/// let mut interner = Interner::new();
+/// let rows = decode_bytes_projected_interned(input, &[1, 2], &mut interner);
+/// assert_eq!(rows[0][0], rows[2][0]);
+/// assert_eq!(interner.resolve(rows[2][1]), b"");
+/// ```
+pub fn decode_bytes_projected_interned(input: &[u8], columns: impl Columns, interner: &mut Interner) -> Vec<Vec<Symbol>> {
+    let Some(columns) = columns.static_columns() else {
+        // Rows of different widths select different columns: no per-column
+        // dictionaries to merge, so intern the decoded cells in order.
+        let rows = crate::decode_bytes_selected(input, &columns.to_selector());
+        return rows.iter().map(|row| row.iter().map(|value| interner.intern(value)).collect()).collect();
+    };
+    decode_interned(input, Some(&columns), interner)
+}
+
+fn decode_interned(input: &[u8], columns: Option<&[usize]>, interner: &mut Interner) -> Vec<Vec<Symbol>> {
+    let pieces = DecodeOptions::default()
+        .decode_pieces(input, |piece| intern_piece(piece, columns), |piece| piece.rows.len())
+        .expect("no cancel token");
+
+    let mut rows = Vec::with_capacity(pieces.iter().map(|piece| piece.rows.len()).sum());
+    for mut piece in pieces {
+        let global: Vec<Symbol> = piece.values.iter().map(|value| interner.intern(value)).collect();
+        for row in &mut piece.rows {
+            for symbol in row.iter_mut() {
+                *symbol = global[symbol.index()];
+            }
+        }
+        rows.append(&mut piece.rows);
+    }
+    rows
+}
+
+/// The rows of one piece of input, in symbols of its own dictionary.
+struct Piece<'a> {
+    values: Vec<Cow<'a, [u8]>>,
+    rows: Vec<Vec<Symbol>>,
+}
+
+/// Decode one piece of input, starting at a row start, into local symbols.
+fn intern_piece<'a>(piece: &'a [u8], columns: Option<&[usize]>) -> Piece<'a> {
+    let mut builder = PieceBuilder {
+        dictionary: HashMap::new(),
+        piece: Piece { values: Vec::new(), rows: Vec::new() },
+        columns,
+        width: columns.map_or(0, |columns| columns.iter().max().map_or(0, |&c| c.saturating_add(1))),
+        raw: Vec::new(),
+        row: Vec::new(),
+        open: false,
+    };
+    let (rest, escaped) = scan_lines(piece, |start, end, escaped| {
+        if end > start {
+            builder.push(&piece[start..end], escaped);
+        } else {
+            builder.end_row();
+        }
+    });
+    if rest < piece.len() {
+        builder.push(&piece[rest..], escaped);
+    }
+    if builder.open {
+        builder.end_row();
+    }
+    builder.piece
+}
+
+struct PieceBuilder<'a, 'c> {
+    dictionary: HashMap<Cow<'a, [u8]>, Symbol>,
+    piece: Piece<'a>,
+    columns: Option<&'c [usize]>,
+    /// Cells past this are never looked at when projecting.
+    width: usize,
+    /// Projected, raw cells are held until the row ends; otherwise each cell
+    /// is interned as it is read.
+    raw: Vec<(&'a [u8], bool)>,
+    row: Vec<Symbol>,
+    open: bool,
+}
+
+impl<'a> PieceBuilder<'a, '_> {
+    fn intern(&mut self, value: Cow<'a, [u8]>) -> Symbol {
+        if let Some(&symbol) = self.dictionary.get(&value) {
+            return symbol;
+        }
+        let symbol = Symbol(self.piece.values.len() as u32);
+        self.piece.values.push(value.clone());
+        self.dictionary.insert(value, symbol);
+        symbol
+    }
+
+    fn push(&mut self, text: &'a [u8], escaped: bool) {
+        self.open = true;
+        if self.columns.is_none() {
+            let symbol = self.intern(cell(text, escaped));
+            self.row.push(symbol);
+        } else if self.raw.len() < self.width {
+            self.raw.push((text, escaped));
+        }
+    }
+
+    fn end_row(&mut self) {
+        if let Some(columns) = self.columns {
+            for &c in columns {
+                let value = self.raw.get(c).map_or(Cow::Borrowed(&b""[..]), |&(text, escaped)| cell(text, escaped));
+                let symbol = self.intern(value);
+                self.row.push(symbol);
+            }
+            self.raw.clear();
+        }
+        self.piece.rows.push(std::mem::take(&mut self.row));
+        self.open = false;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolved(interner: &Interner, rows: &[Vec<Symbol>]) -> Vec<Vec<Vec<u8>>> {
+        rows.iter().map(|row| row.iter().map(|&s| interner.resolve(s).to_vec()).collect()).collect()
+    }
+
+    #[test]
+    fn test_interner() {
+        let mut interner = Interner::new();
+        let a = interner.intern(b"a");
+        let b = interner.intern(b"b\n");
+        assert_eq!(interner.intern(b"a"), a);
+        assert_ne!(a, b);
+        assert_eq!((a.index(), b.index()), (0, 1));
+        assert_eq!(interner.get(b"b\n"), Some(b));
+        assert_eq!(interner.get(b"c"), None);
+        assert_eq!(&*interner.resolve_shared(b), b"b\n");
+        assert_eq!(interner.iter().collect::<Vec<_>>(), [(a, &b"a"[..]), (b, b"b\n")]);
+    }
+
+    #[test]
+    fn test_decode_bytes_interned() {
+        for input in [
+            &b""[..],
+            b"\n\n",
+            b"FR\nEUR\n\nDE\nEUR\n\nFR\n\\\n\n",
+            b"a\\nb\n\\\\\n\nx\\q\n\\\n\nunterminated",
+        ] {
+            let expected: Vec<Vec<Vec<u8>>> = crate::decode_bytes(input).into_iter().map(|row| row.into_iter().map(Cow::into_owned).collect()).collect();
+            let mut interner = Interner::new();
+            let rows = decode_bytes_interned(input, &mut interner);
+            assert_eq!(resolved(&interner, &rows), expected);
+
+            let distinct: std::collections::HashSet<_> = expected.iter().flatten().collect();
+            assert_eq!(interner.len(), distinct.len());
+
+            let projected: Vec<Vec<Vec<u8>>> = crate::decode_bytes_projected(input, &[1, 0, 4])
+                .into_iter()
+                .map(|row| row.into_iter().map(Cow::into_owned).collect())
+                .collect();
+            let rows = decode_bytes_projected_interned(input, &[1, 0, 4], &mut interner);
+            assert_eq!(resolved(&interner, &rows), projected);
+
+            let last = crate::ColumnSelector::new().index(-1).index(0);
+            let selected: Vec<Vec<Vec<u8>>> = crate::decode_bytes_selected(input, &last)
+                .into_iter()
+                .map(|row| row.into_iter().map(Cow::into_owned).collect())
+                .collect();
+            let rows = decode_bytes_projected_interned(input, &last, &mut interner);
+            assert_eq!(resolved(&interner, &rows), selected);
+        }
+    }
+
+    #[test]
+    fn test_pieces_merge_in_order() {
+        let data: Vec<Vec<String>> = (0..20_000).map(|i| vec![format!("c{}", i % 7), format!("s\\{}", i % 3), format!("{}", i)]).collect();
+        let encoded = crate::encode(&data);
+        assert!(encoded.len() > crate::PARALLEL_THRESHOLD);
+
+        // The interner already holds a value; new ones follow in order of appearance
+        let mut interner = Interner::new();
+        let preset = interner.intern(b"s\\2");
+        let rows = decode_bytes_interned(encoded.as_bytes(), &mut interner);
+        let expected: Vec<Vec<Vec<u8>>> = data.iter().map(|row| row.iter().map(|c| c.as_bytes().to_vec()).collect()).collect();
+        assert_eq!(resolved(&interner, &rows), expected);
+        assert_eq!(rows[2][1], preset);
+        let order: Vec<&[u8]> = interner.iter().take(4).map(|(_, value)| value).collect();
+        assert_eq!(order, [&b"s\\2"[..], b"c0", b"s\\0", b"0"]);
+
+        // Same symbols however the input is cut
+        for n in [1, 3, 50] {
+            let mut by_pieces = Interner::new();
+            by_pieces.intern(b"s\\2");
+            let points = crate::boundaries::split_points(encoded.as_bytes(), n);
+            let mut rows = Vec::new();
+            for w in points.windows(2) {
+                let piece = intern_piece(&encoded.as_bytes()[w[0]..w[1]], Some(&[1, 0]));
+                let global: Vec<Symbol> = piece.values.iter().map(|v| by_pieces.intern(v)).collect();
+                rows.extend(piece.rows.into_iter().map(|row| row.into_iter().map(|s| global[s.index()]).collect::<Vec<_>>()));
+            }
+            let mut whole = Interner::new();
+            whole.intern(b"s\\2");
+            assert_eq!(rows, decode_bytes_projected_interned(encoded.as_bytes(), &[1, 0], &mut whole), "{} pieces", n);
+            assert_eq!(by_pieces.len(), whole.len());
+        }
+    }
+}
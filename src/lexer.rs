@@ -0,0 +1,313 @@
+//! NSV's lexical structure, as a stream of events over the raw bytes.
+//!
+//! Every line of the input is either a cell or, when empty, the end of a row.
+//! A cell line is made of literal text and escape sequences; [`lex`] reports
+//! each of them with its byte range, then the LF that ends the cell. The
+//! decoders and [`check`](crate::check) read the input the same way:
+//!
+//! | Bytes | Event | Decodes to |
+//! |-------|-------|------------|
+//! | run without `\` or LF | [`Event::CellText`] | itself |
+//! | `\n` | [`Event::Escape`], [`EscapeKind::Newline`] | LF |
+//! | `\\` | [`Event::Escape`], [`EscapeKind::Backslash`] | `\` |
+//! | `\` and another byte | [`Event::Escape`], [`EscapeKind::Unknown`] | both bytes, unchanged |
+//! | `\` ending a line with more before it, or ending the input | [`Event::Escape`], [`EscapeKind::Dangling`] | nothing |
+//! | `\` alone on an LF-terminated line | [`Event::EmptyCellToken`] | an empty cell |
+//! | LF ending a cell line | [`Event::CellEnd`] | |
+//! | LF alone on its line | [`Event::RowEnd`] | |
+//!
+//! A last row cut off by the end of the input, without its empty line, still
+//! counts when it has cells.
+
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+/// One lexical element of the input. Ranges and offsets are in bytes.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Event {
+    /// Literal cell bytes, without backslashes or LFs.
+    CellText(Range<usize>),
+    /// A backslash sequence inside a cell.
+    Escape { kind: EscapeKind, range: Range<usize> },
+    /// The `\` of a line holding nothing else: an empty cell.
+    EmptyCellToken(Range<usize>),
+    /// End of a cell: the offset of its LF, or the input's length for a
+    /// cell cut off by the end of the input.
+    CellEnd(usize),
+    /// End of a row: the offset of its empty line's LF.
+    RowEnd(usize),
+    /// End of the input, always the last event. `terminated` is whether the
+    /// input is empty or ends with an LF.
+    Eof { terminated: bool },
+}
+
+/// What a backslash sequence stands for. See [`Event::Escape`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EscapeKind {
+    /// `\n`, an LF.
+    Newline,
+    /// `\\`, a backslash.
+    Backslash,
+    /// `\` followed by this byte, kept as is.
+    Unknown(u8),
+    /// A lone `\` at the end of a line or of the input, dropped.
+    Dangling,
+}
+
+/// Iterate over the lexical events of `input`.
+///
+/// ```
+/// use nsv::lexer::{Event, EscapeKind};
+///
+/// let events: Vec<Event> = nsv::lex(b"a\\n\n\\\n\n").collect();
+/// assert_eq!(events, [
+///     Event::CellText(0..1),
+///     Event::Escape { kind: EscapeKind::Newline, range: 1..3 },
+///     Event::CellEnd(3),
+///     Event::EmptyCellToken(4..5),
+///     Event::CellEnd(5),
+///     Event::RowEnd(6),
+///     Event::Eof { terminated: true },
+/// ]);
+/// ```
+pub fn lex(input: &[u8]) -> Lexer<'_> {
+    Lexer { input, pos: 0, line_end: None, done: false }
+}
+
+/// Iterator over the events of an input. See [`lex`].
+#[derive(Debug, Clone)]
+pub struct Lexer<'a> {
+    input: &'a [u8],
+    pos: usize,
+    /// Inside a cell line: where it ends.
+    line_end: Option<usize>,
+    done: bool,
+}
+
+impl Lexer<'_> {
+    /// The next event inside the cell line ending at `end`.
+    fn cell_event(&mut self, end: usize) -> Event {
+        let (input, pos) = (self.input, self.pos);
+        if pos == end {
+            self.line_end = None;
+            self.pos = (end + 1).min(input.len());
+            return Event::CellEnd(end);
+        }
+        if input[pos] != b'\\' {
+            let text_end = memchr::memchr(b'\\', &input[pos..end]).map_or(end, |at| pos + at);
+            self.pos = text_end;
+            return Event::CellText(pos..text_end);
+        }
+        let kind = match input.get(pos + 1) {
+            _ if pos + 1 == end => EscapeKind::Dangling,
+            Some(b'n') => EscapeKind::Newline,
+            Some(b'\\') => EscapeKind::Backslash,
+            Some(&b) => EscapeKind::Unknown(b),
+            None => unreachable!("a line ends before the end of the input"),
+        };
+        let len = if kind == EscapeKind::Dangling { 1 } else { 2 };
+        self.pos = pos + len;
+        Event::Escape { kind, range: pos..pos + len }
+    }
+}
+
+impl Iterator for Lexer<'_> {
+    type Item = Event;
+
+    fn next(&mut self) -> Option<Event> {
+        if let Some(end) = self.line_end {
+            return Some(self.cell_event(end));
+        }
+        if self.done {
+            return None;
+        }
+
+        let (input, pos) = (self.input, self.pos);
+        if pos == input.len() {
+            self.done = true;
+            return Some(Event::Eof { terminated: input.last().is_none_or(|&b| b == b'\n') });
+        }
+        if input[pos] == b'\n' {
+            self.pos += 1;
+            return Some(Event::RowEnd(pos));
+        }
+
+        let end = memchr::memchr(b'\n', &input[pos..]).map_or(input.len(), |at| pos + at);
+        self.line_end = Some(end);
+        if input[pos..end] == *b"\\" && end < input.len() {
+            self.pos = end;
+            return Some(Event::EmptyCellToken(pos..end));
+        }
+        Some(self.cell_event(end))
+    }
+}
+
+impl FusedIterator for Lexer<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Every input of up to `max_len` bytes over the bytes that matter.
+    fn all_inputs(max_len: usize) -> Vec<Vec<u8>> {
+        let mut inputs = vec![Vec::new()];
+        let mut last = vec![Vec::new()];
+        for _ in 0..max_len {
+            last = last.iter().flat_map(|prefix: &Vec<u8>| b"\n\\nx".iter().map(move |&b| [&prefix[..], &[b]].concat())).collect();
+            inputs.extend(last.iter().cloned());
+        }
+        inputs
+    }
+
+    /// Rows as read from the events alone.
+    fn rows_from_events(input: &[u8]) -> Vec<Vec<Vec<u8>>> {
+        let (mut rows, mut row, mut cell) = (Vec::new(), Vec::new(), Vec::new());
+        for event in lex(input) {
+            match event {
+                Event::CellText(range) => cell.extend_from_slice(&input[range]),
+                Event::Escape { kind: EscapeKind::Newline, .. } => cell.push(b'\n'),
+                Event::Escape { kind: EscapeKind::Backslash, .. } => cell.push(b'\\'),
+                Event::Escape { kind: EscapeKind::Unknown(_), range } => cell.extend_from_slice(&input[range]),
+                Event::Escape { kind: EscapeKind::Dangling, .. } | Event::EmptyCellToken(_) => {}
+                Event::CellEnd(_) => row.push(std::mem::take(&mut cell)),
+                Event::RowEnd(_) => rows.push(std::mem::take(&mut row)),
+                Event::Eof { .. } if !row.is_empty() => rows.push(std::mem::take(&mut row)),
+                Event::Eof { .. } => {}
+            }
+        }
+        rows
+    }
+
+    #[test]
+    fn test_events_decode_like_decoder() {
+        for input in all_inputs(7) {
+            let decoded: Vec<Vec<Vec<u8>>> = crate::decode_rows(&crate::AllColumns, &input)
+                .into_iter()
+                .map(|row| row.into_iter().map(|c| c.into_owned()).collect())
+                .collect();
+            assert_eq!(rows_from_events(&input), decoded, "{:?}", String::from_utf8_lossy(&input));
+        }
+    }
+
+    /// The byte-at-a-time `check` that predates the lexer.
+    fn check_bytewise(input: &[u8]) -> Vec<crate::Warning> {
+        if input.is_empty() {
+            return Vec::new();
+        }
+
+        let mut warnings = Vec::new();
+        let len = input.len();
+        let mut line: usize = 1;
+        let mut line_start: usize = 0;
+        let mut escaped = false;
+
+        for (i, &b) in input.iter().enumerate() {
+            if escaped {
+                match b {
+                    b'n' | b'\\' => {}
+                    b'\n' if i - 1 != line_start => warnings.push(crate::Warning {
+                        kind: crate::WarningKind::DanglingBackslash,
+                        pos: i - 1,
+                        line,
+                        col: i - line_start,
+                    }),
+                    b'\n' => {}
+                    _ => warnings.push(crate::Warning {
+                        kind: crate::WarningKind::UnknownEscape(b),
+                        pos: i - 1,
+                        line,
+                        col: i - line_start,
+                    }),
+                }
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            }
+            if b == b'\n' {
+                line += 1;
+                line_start = i + 1;
+            }
+        }
+
+        if escaped {
+            warnings.push(crate::Warning {
+                kind: crate::WarningKind::DanglingBackslash,
+                pos: len - 1,
+                line,
+                col: len - line_start,
+            });
+        }
+
+        if line_start != len {
+            warnings.push(crate::Warning {
+                kind: crate::WarningKind::NoTerminalLf,
+                pos: len,
+                line,
+                col: len - line_start + 1,
+            });
+        }
+
+        warnings
+    }
+
+    #[test]
+    fn test_check_matches_bytewise() {
+        for input in all_inputs(7) {
+            assert_eq!(crate::check(&input), check_bytewise(&input), "{:?}", String::from_utf8_lossy(&input));
+        }
+    }
+
+    #[test]
+    fn test_events_tile_input() {
+        for input in all_inputs(7) {
+            // Events cover every byte exactly once, in order
+            let mut pos = 0;
+            let mut saw_eof = false;
+            for event in lex(&input) {
+                assert!(!saw_eof);
+                match event {
+                    Event::CellText(range) | Event::Escape { range, .. } | Event::EmptyCellToken(range) => {
+                        assert_eq!(range.start, pos);
+                        assert!(range.end > range.start);
+                        pos = range.end;
+                    }
+                    Event::CellEnd(at) | Event::RowEnd(at) => {
+                        assert_eq!(at, pos);
+                        pos = (at + 1).min(input.len());
+                    }
+                    Event::Eof { terminated } => {
+                        assert_eq!(terminated, input.is_empty() || input.ends_with(b"\n"));
+                        saw_eof = true;
+                    }
+                }
+            }
+            assert!(saw_eof);
+            assert_eq!(pos, input.len(), "{:?}", String::from_utf8_lossy(&input));
+        }
+    }
+
+    #[test]
+    fn test_lex_edge_cases() {
+        use Event::*;
+        assert_eq!(lex(b"").collect::<Vec<_>>(), [Eof { terminated: true }]);
+        assert_eq!(lex(b"\n").collect::<Vec<_>>(), [RowEnd(0), Eof { terminated: true }]);
+        assert_eq!(
+            lex(b"a\\").collect::<Vec<_>>(),
+            [CellText(0..1), Escape { kind: EscapeKind::Dangling, range: 1..2 }, CellEnd(2), Eof { terminated: false }]
+        );
+        // A lone backslash at the end of the input is dangling, not an empty cell token
+        assert_eq!(lex(b"\\").collect::<Vec<_>>(), [Escape { kind: EscapeKind::Dangling, range: 0..1 }, CellEnd(1), Eof { terminated: false }]);
+        assert_eq!(
+            lex(b"\\q\\\\\n").collect::<Vec<_>>(),
+            [
+                Escape { kind: EscapeKind::Unknown(b'q'), range: 0..2 },
+                Escape { kind: EscapeKind::Backslash, range: 2..4 },
+                CellEnd(4),
+                Eof { terminated: true },
+            ]
+        );
+        let mut lexer = lex(b"x");
+        assert_eq!(lexer.by_ref().count(), 3);
+        assert_eq!(lexer.next(), None);
+    }
+}
@@ -5,34 +5,90 @@
 //!
 //! ## Parallel Parsing Strategy
 //!
-//! For files larger than 64KB, we use a chunked parallel approach:
-//! 1. Pick N evenly-spaced byte positions (one per CPU core)
-//! 2. For each, scan forward to the nearest `\n\n` row boundary — O(avg_row_len)
-//! 3. Each worker independently parses its chunk (boundary scan + cell split + unescape)
+//! For files larger than 256 KiB, we use a chunked parallel approach:
+//! 1. Pick evenly-spaced byte positions — several per CPU core, so rayon can
+//!    balance skewed inputs by work stealing
+//! 2. For each, scan forward to the next line start — O(avg_cell_len)
+//! 3. Count each chunk's row ends in parallel (LF scan only), giving every chunk
+//!    its first row index and first column, and the exact size of the result
+//! 4. Each worker parses its chunk with the two-stage scan below, straight into
+//!    its slice of the result; rows that straddle a split are stitched in order
 //!
 //! This works because literal `0x0A` bytes in NSV are always structural (never escaped),
-//! so row alignment recovery from any byte position is a trivial forward scan.
-//! The sequential phase is O(N), not O(input_len) — all real work is parallel.
+//! so cell alignment recovery from any byte position is a trivial forward scan.
+//! Splitting at cells rather than rows means a single huge row still uses every core.
+//! The sequential phase is O(chunks), not O(input_len) — all real work is parallel.
 //!
 //! For smaller files, we use a sequential fast path to avoid thread overhead.
-
+//! [`DecodeOptions`] overrides the threshold, forces sequential decoding,
+//! runs the parallel decoder on a dedicated thread pool, and hooks up progress
+//! reporting and cancellation.
+//!
+//! Every decoder, sequential or per-chunk, runs the same two-stage scan: stage 1
+//! indexes LF positions with memchr2 (flagging lines that contain a backslash),
+//! stage 2 slices cells from that index and unescapes only the flagged ones.
+//!
+//! Encoding above the same threshold splits the rows into contiguous ranges,
+//! escapes each range into its own buffer in parallel, and joins them in order.
+
+pub mod boundaries;
+pub mod document;
+pub mod intern;
+pub mod lexer;
+pub mod table;
 pub mod util;
 
+mod selector;
+#[cfg(test)]
+mod test_util;
+pub use document::IncrementalDocument;
+pub use intern::{decode_bytes_interned, decode_bytes_projected_interned, Interner, Symbol};
+pub use lexer::lex;
+pub use selector::{ColumnSelector, Columns};
+pub use table::Table;
+
+#[cfg(feature = "mmap")]
+pub mod mmap;
+
+#[cfg(feature = "lsp")]
+pub mod lsp;
+
+#[cfg(feature = "bytes")]
+mod shared;
+#[cfg(feature = "bytes")]
+pub use shared::{decode_shared, decode_shared_with};
+
+#[cfg(feature = "parallel")]
 use memchr::memmem;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use std::borrow::Cow;
-use std::io::{self, Read, Write};
+use std::fmt;
+use std::io::{self, BufRead, Read, Write};
+use std::ops::Range;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::Arc;
+
+use lexer::{EscapeKind, Event};
 
 pub const VERSION: &str = env!("CARGO_PKG_VERSION");
 
-/// Threshold for using parallel parsing (bytes)
-const PARALLEL_THRESHOLD: usize = 256 * 1024;
+/// Default input size (bytes) from which decoding and encoding go parallel.
+///
+/// Override per call with [`DecodeOptions::parallel_threshold`].
+pub const PARALLEL_THRESHOLD: usize = 256 * 1024;
 
 /// Decode an NSV string into a seqseq.
 pub fn decode(s: &str) -> Vec<Vec<String>> {
-    decode_bytes(s.as_bytes())
+    decode_with(s, &DecodeOptions::default()).expect("no cancel token")
+}
+
+/// [`decode`] with explicit [`DecodeOptions`].
+///
+/// Fails only when the options' [`CancelToken`] is cancelled mid-decode.
+pub fn decode_with(s: &str, options: &DecodeOptions) -> Result<Vec<Vec<String>>, Cancelled> {
+    let rows = decode_bytes_with(s.as_bytes(), options)?
         .into_iter()
         .map(|row| {
             row.into_iter()
@@ -44,7 +100,8 @@ pub fn decode(s: &str) -> Vec<Vec<String>> {
                 })
                 .collect()
         })
-        .collect()
+        .collect();
+    Ok(rows)
 }
 
 /// Decode raw bytes into a seqseq of byte slices.
@@ -53,98 +110,685 @@ pub fn decode(s: &str) -> Vec<Vec<String>> {
 /// Cells are returned as `Cow<[u8]>` — borrowed when no unescaping was needed
 /// (zero-copy), owned when the cell contained escape sequences.
 pub fn decode_bytes<'a>(input: &'a [u8]) -> Vec<Vec<Cow<'a, [u8]>>> {
+    decode_bytes_with(input, &DecodeOptions::default()).expect("no cancel token")
+}
+
+/// [`decode_bytes`] with explicit [`DecodeOptions`].
+///
+/// Fails only when the options' [`CancelToken`] is cancelled mid-decode.
+pub fn decode_bytes_with<'a>(input: &'a [u8], options: &DecodeOptions) -> Result<Vec<Vec<Cow<'a, [u8]>>>, Cancelled> {
     if input.is_empty() {
-        return Vec::new();
+        return Ok(Vec::new());
     }
 
+    options.decode_rows(&AllColumns, input)
+}
+
+/// Decode at most `take` rows, starting from row `skip`.
+///
+/// Skipped rows are only scanned for their row ends, and scanning stops after
+/// the last row taken.
+///
+/// ```
+/// let input = b"h\n\n1\n\n2\n\n3\n\n";
+/// assert_eq!(nsv::decode_bytes_range(input, 2, 2), [[&b"2"[..]], [&b"3"[..]]]);
+/// ```
+pub fn decode_bytes_range(input: &[u8], skip: usize, take: usize) -> Vec<Vec<Cow<'_, [u8]>>> {
+    let rest = &input[boundaries::nth_row_start(input, skip)..];
+    decode_bytes(&rest[..boundaries::nth_row_start(rest, take)])
+}
+
+// ── Decode options ───────────────────────────────────────────────────
+
+/// Controls how the `*_with` decoders schedule their work.
+///
+/// ```
+/// use nsv::{decode_bytes_with, DecodeOptions};
+///
+/// // Latency-sensitive caller: never touch the thread pool.
+/// let options = DecodeOptions::new().sequential(true);
+/// let rows = decode_bytes_with(b"a\nb\n\n", &options).unwrap();
+/// assert_eq!(rows.len(), 1);
+/// ```
+#[derive(Clone)]
+pub struct DecodeOptions {
+    parallel_threshold: usize,
+    sequential: bool,
     #[cfg(feature = "parallel")]
-    if input.len() >= PARALLEL_THRESHOLD {
-        return decode_bytes_parallel(input);
+    thread_pool: Option<Arc<rayon::ThreadPool>>,
+    on_progress: Option<Arc<dyn Fn(Progress) + Send + Sync>>,
+    cancel_token: Option<CancelToken>,
+}
+
+impl fmt::Debug for DecodeOptions {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut s = f.debug_struct("DecodeOptions");
+        s.field("parallel_threshold", &self.parallel_threshold);
+        s.field("sequential", &self.sequential);
+        #[cfg(feature = "parallel")]
+        s.field("thread_pool", &self.thread_pool);
+        s.field("on_progress", &self.on_progress.is_some());
+        s.field("cancel_token", &self.cancel_token);
+        s.finish()
+    }
+}
+
+impl Default for DecodeOptions {
+    fn default() -> Self {
+        DecodeOptions {
+            parallel_threshold: PARALLEL_THRESHOLD,
+            sequential: false,
+            #[cfg(feature = "parallel")]
+            thread_pool: None,
+            on_progress: None,
+            cancel_token: None,
+        }
+    }
+}
+
+impl DecodeOptions {
+    /// Default options: parallel from [`PARALLEL_THRESHOLD`] bytes, on the global rayon pool.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Input size (bytes) from which the parallel decoder is used.
+    pub fn parallel_threshold(mut self, bytes: usize) -> Self {
+        self.parallel_threshold = bytes;
+        self
+    }
+
+    /// Always decode on the calling thread, whatever the input size.
+    pub fn sequential(mut self, sequential: bool) -> Self {
+        self.sequential = sequential;
+        self
+    }
+
+    /// Run parallel decodes on `pool` instead of the global rayon pool.
+    #[cfg(feature = "parallel")]
+    pub fn thread_pool(mut self, pool: Arc<rayon::ThreadPool>) -> Self {
+        self.thread_pool = Some(pool);
+        self
+    }
+
+    /// Call `f` with the running totals as the input is decoded.
+    ///
+    /// Reports come every few MiB of input. Parallel decodes report from the
+    /// worker threads, possibly concurrently, so totals may arrive out of order;
+    /// the last report of a completed decode covers the whole input.
+    pub fn on_progress(mut self, f: impl Fn(Progress) + Send + Sync + 'static) -> Self {
+        self.on_progress = Some(Arc::new(f));
+        self
+    }
+
+    /// Stop decoding with [`Cancelled`] once `token` is cancelled.
+    pub fn cancel_token(mut self, token: CancelToken) -> Self {
+        self.cancel_token = Some(token);
+        self
+    }
+
+    /// Whether an input of `len` bytes is decoded in parallel.
+    #[cfg(feature = "parallel")]
+    fn is_parallel(&self, len: usize) -> bool {
+        !self.sequential && len >= self.parallel_threshold
+    }
+
+    /// Decode `input` with `sink`, sequentially or in parallel as configured.
+    fn decode_rows<'a, S: RowSink<'a>>(&self, sink: &S, input: &'a [u8]) -> Result<Vec<S::Row>, Cancelled> {
+        let tracker = Tracker {
+            on_progress: self.on_progress.as_deref(),
+            cancel_token: self.cancel_token.as_ref(),
+            ..Tracker::default()
+        };
+
+        #[cfg(feature = "parallel")]
+        if self.is_parallel(input.len()) {
+            return match &self.thread_pool {
+                Some(pool) => pool.install(|| decode_rows_parallel(sink, input, &tracker)),
+                None => decode_rows_parallel(sink, input, &tracker),
+            };
+        }
+
+        decode_rows_tracked(sink, input, &tracker)
+    }
+
+    /// Map decoded `rows` of an input of `len` bytes through `f`, in parallel
+    /// when the decode itself would be.
+    fn map_rows<T: Send, U: Send>(&self, len: usize, rows: Vec<T>, f: impl Fn(T) -> U + Sync + Send) -> Vec<U> {
+        #[cfg(feature = "parallel")]
+        if self.is_parallel(len) {
+            let map = || rows.into_par_iter().map(f).collect();
+            return match &self.thread_pool {
+                Some(pool) => pool.install(map),
+                None => map(),
+            };
+        }
+
+        let _ = len;
+        rows.into_iter().map(f).collect()
+    }
+
+    /// Cut `input` at row starts and run `decode` on each piece, in parallel
+    /// when configured so; `rows` counts the rows of a decoded piece, for
+    /// progress reports.
+    ///
+    /// For decoders that build their own per-piece results rather than rows.
+    pub(crate) fn decode_pieces<'a, T: Send>(
+        &self,
+        input: &'a [u8],
+        decode: impl Fn(&'a [u8]) -> T + Sync,
+        rows: impl Fn(&T) -> usize + Sync,
+    ) -> Result<Vec<T>, Cancelled> {
+        let tracker = Tracker {
+            on_progress: self.on_progress.as_deref(),
+            cancel_token: self.cancel_token.as_ref(),
+            ..Tracker::default()
+        };
+        let tracked = |piece: &'a [u8]| {
+            tracker.check()?;
+            let decoded = decode(piece);
+            tracker.advance(piece.len(), rows(&decoded));
+            Ok(decoded)
+        };
+        let tracking_pieces = if tracker.is_active() { input.len() / TRACKING_INTERVAL } else { 1 };
+
+        #[cfg(feature = "parallel")]
+        if self.is_parallel(input.len()) {
+            // Counted inside the pool, so pieces are sized for its threads
+            let run = || {
+                let points = boundaries::split_points(input, row_chunk_count(input.len()).max(tracking_pieces));
+                points.par_windows(2).map(|w| tracked(&input[w[0]..w[1]])).collect()
+            };
+            return match &self.thread_pool {
+                Some(pool) => pool.install(run),
+                None => run(),
+            };
+        }
+
+        let points = boundaries::split_points(input, tracking_pieces);
+        points.windows(2).map(|w| tracked(&input[w[0]..w[1]])).collect()
+    }
+}
+
+/// Running totals passed to a [`DecodeOptions::on_progress`] callback.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Progress {
+    /// Input bytes decoded so far.
+    pub bytes: usize,
+    /// Rows produced so far.
+    pub rows: usize,
+}
+
+/// Shared flag that stops a decode started with [`DecodeOptions::cancel_token`].
+///
+/// Clones share the flag, so one can be handed to a Ctrl-C handler while
+/// another drives the decode.
+#[derive(Debug, Clone, Default)]
+pub struct CancelToken(Arc<AtomicBool>);
+
+impl CancelToken {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Ask every decode using this token to stop.
+    pub fn cancel(&self) {
+        self.0.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// The decode was stopped through its [`CancelToken`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cancelled;
+
+impl fmt::Display for Cancelled {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("decode cancelled")
+    }
+}
+
+impl std::error::Error for Cancelled {}
+
+/// Input decoded between progress reports and cancellation checks.
+const TRACKING_INTERVAL: usize = 4 * 1024 * 1024;
+
+/// Progress and cancellation state of one decode call.
+#[derive(Default)]
+struct Tracker<'o> {
+    on_progress: Option<&'o (dyn Fn(Progress) + Send + Sync)>,
+    cancel_token: Option<&'o CancelToken>,
+    bytes: AtomicUsize,
+    rows: AtomicUsize,
+}
+
+impl Tracker<'_> {
+    /// Whether anyone is watching; if not, decoders skip tracking entirely.
+    fn is_active(&self) -> bool {
+        self.on_progress.is_some() || self.cancel_token.is_some()
+    }
+
+    fn check(&self) -> Result<(), Cancelled> {
+        match self.cancel_token {
+            Some(token) if token.is_cancelled() => Err(Cancelled),
+            _ => Ok(()),
+        }
     }
 
-    decode_bytes_sequential(input)
+    /// Record `bytes` more input decoded into `rows` more rows.
+    fn advance(&self, bytes: usize, rows: usize) {
+        if let Some(on_progress) = self.on_progress {
+            let bytes = self.bytes.fetch_add(bytes, Ordering::Relaxed) + bytes;
+            let rows = self.rows.fetch_add(rows, Ordering::Relaxed) + rows;
+            on_progress(Progress { bytes, rows });
+        }
+    }
 }
 
-/// Sequential implementation (byte-level).
-fn decode_bytes_sequential<'a>(input: &'a [u8]) -> Vec<Vec<Cow<'a, [u8]>>> {
-    let mut data = Vec::new();
-    let mut row: Vec<Cow<'a, [u8]>> = Vec::new();
+// ── Structural index ─────────────────────────────────────────────────
+//
+// Two-stage scan shared by every decoder. Stage 1 jumps between structural
+// bytes (`\n`, `\`) with memchr2 and records a batch of LF positions, each
+// flagged when its line contains a backslash; once a backslash is seen the
+// rest of the line is skipped with memchr. Stage 2 walks the batch, slicing
+// cells without looking at their bytes again and unescaping only the flagged
+// ones. Batches are fixed-size, so the index lives on the stack.
+
+/// LF positions recorded per stage-1 batch.
+const INDEX_BATCH: usize = 1024;
+
+/// Flag bit on an index entry: the line it terminates contains a backslash.
+const ESCAPED: usize = 1 << (usize::BITS - 1);
+
+/// Call `line(start, end, escaped)` for every LF-terminated line in `input`,
+/// where `input[start..end]` is the line without its LF and `escaped` is set
+/// when it contains a backslash.
+///
+/// Returns `(start, escaped)` for the unterminated remainder after the last LF
+/// (`start == input.len()` when there is none).
+#[inline]
+fn scan_lines(input: &[u8], mut line: impl FnMut(usize, usize, bool)) -> (usize, bool) {
+    let mut index = [0usize; INDEX_BATCH];
+    let mut pos = 0;
     let mut start = 0;
+    let mut escaped = false;
 
-    for (pos, &b) in input.iter().enumerate() {
-        if b == b'\n' {
-            if pos > start {
-                row.push(unescape_bytes(&input[start..pos]));
+    loop {
+        // Stage 1: fill the batch.
+        let mut n = 0;
+        while n < INDEX_BATCH {
+            let Some(offset) = memchr::memchr2(b'\n', b'\\', &input[pos..]) else {
+                pos = input.len();
+                break;
+            };
+            let at = pos + offset;
+            if input[at] == b'\n' {
+                index[n] = if escaped { at | ESCAPED } else { at };
+                escaped = false;
+                pos = at + 1;
+                n += 1;
             } else {
-                data.push(row);
-                row = Vec::new();
+                escaped = true;
+                match memchr::memchr(b'\n', &input[at + 1..]) {
+                    Some(offset) => {
+                        let lf = at + 1 + offset;
+                        index[n] = lf | ESCAPED;
+                        escaped = false;
+                        pos = lf + 1;
+                        n += 1;
+                    }
+                    None => {
+                        pos = input.len();
+                        break;
+                    }
+                }
             }
-            start = pos + 1;
         }
+
+        // Stage 2: hand out the lines.
+        for &entry in &index[..n] {
+            let end = entry & !ESCAPED;
+            line(start, end, entry & ESCAPED != 0);
+            start = end + 1;
+        }
+
+        if pos == input.len() {
+            return (start, escaped);
+        }
+    }
+}
+
+/// Turn a raw cell into its value, unescaping only when stage 1 saw a backslash.
+#[inline]
+fn cell(raw: &[u8], escaped: bool) -> Cow<'_, [u8]> {
+    if escaped {
+        unescape_bytes(raw)
+    } else {
+        Cow::Borrowed(raw)
+    }
+}
+
+// ── Chunked decoding ─────────────────────────────────────────────────
+//
+// The decoders are written once, against `RowSink`, which says how raw cells
+// become rows. Sequentially, `decode_chunk` runs over the whole input. In
+// parallel, the input is cut at line starts into many more chunks than threads,
+// so rayon can steal work when some regions are much denser than others, and
+// decoded in two phases:
+//
+// 1. Each chunk counts its row ends and the cells before the first / after the
+//    last one (`ChunkShape`, LF scan only). A prefix sum over the shapes gives
+//    every chunk the index of its first row and the column of its first cell,
+//    and the exact row count of the result.
+// 2. The result vector is allocated once at that size, carved into disjoint
+//    per-chunk slices (the rows that start in each chunk), and every chunk
+//    decodes straight into its slice. The leading cells of a chunk that starts
+//    mid-row are returned instead and appended to their row afterwards.
+
+/// Oversubscription factor: chunks per rayon thread.
+#[cfg(feature = "parallel")]
+const CHUNKS_PER_THREAD: usize = 8;
+
+/// Lower bound on chunk size, so per-chunk overhead stays negligible.
+#[cfg(feature = "parallel")]
+const MIN_CHUNK_SIZE: usize = 32 * 1024;
+
+/// How decoded cells are assembled into rows.
+trait RowSink<'a>: Sync {
+    type Row: Default + Send;
+
+    /// A new, empty row.
+    fn row(&self) -> Self::Row;
+
+    /// Store the cell at column `col` of `row`. `escaped` is set when `raw`
+    /// contains a backslash.
+    fn push(&self, row: &mut Self::Row, col: usize, raw: &'a [u8], escaped: bool);
+
+    /// Complete `row` with `rest`: its cells from column `first_col` on,
+    /// decoded by a later chunk.
+    #[cfg_attr(not(feature = "parallel"), allow(dead_code))]
+    fn join(&self, row: &mut Self::Row, rest: Self::Row, first_col: usize);
+}
+
+/// Every cell, in order.
+struct AllColumns;
+
+impl<'a> RowSink<'a> for AllColumns {
+    type Row = Vec<Cow<'a, [u8]>>;
+
+    fn row(&self) -> Self::Row {
+        Vec::new()
     }
 
-    if start < input.len() {
-        row.push(unescape_bytes(&input[start..]));
+    #[inline]
+    fn push(&self, row: &mut Self::Row, _col: usize, raw: &'a [u8], escaped: bool) {
+        row.push(cell(raw, escaped));
     }
 
-    if !row.is_empty() {
-        data.push(row);
+    fn join(&self, row: &mut Self::Row, rest: Self::Row, _first_col: usize) {
+        row.extend(rest);
     }
+}
 
-    data
+/// Decode all of `input` on the current thread.
+fn decode_rows<'a, S: RowSink<'a>>(sink: &S, input: &'a [u8]) -> Vec<S::Row> {
+    let mut rows = Vec::new();
+    decode_chunk(sink, input, 0, |row| rows.push(row));
+    rows
 }
 
-/// Chunked parallel implementation for large inputs (byte-level).
+/// Decode `chunk`, which starts at a line start whose cell is at column
+/// `first_col` of its row, passing each row that starts inside the chunk to
+/// `emit` in order (including a final row the chunk leaves open).
 ///
-/// Splits the input into N equal-sized byte chunks (one per core), aligns each
-/// split point to the nearest `\n\n` row boundary, and parses each chunk
-/// independently. The sequential phase is O(N), not O(input_len).
+/// When `first_col > 0` the chunk continues a row begun earlier; its cells up
+/// to the first row end are returned rather than emitted.
+fn decode_chunk<'a, S: RowSink<'a>>(
+    sink: &S,
+    chunk: &'a [u8],
+    first_col: usize,
+    mut emit: impl FnMut(S::Row),
+) -> Option<S::Row> {
+    let mut continued = first_col > 0;
+    let mut head = None;
+    let mut row = sink.row();
+    let mut col = first_col;
+
+    let (rest, escaped) = scan_lines(chunk, |start, end, escaped| {
+        if end > start {
+            sink.push(&mut row, col, &chunk[start..end], escaped);
+            col += 1;
+        } else {
+            let done = std::mem::replace(&mut row, sink.row());
+            if continued {
+                head = Some(done);
+                continued = false;
+            } else {
+                emit(done);
+            }
+            col = 0;
+        }
+    });
+
+    if rest < chunk.len() {
+        sink.push(&mut row, col, &chunk[rest..], escaped);
+        col += 1;
+    }
+
+    if continued {
+        // No row end in this chunk: all of it continues the earlier row.
+        return Some(row);
+    }
+    if col > 0 {
+        emit(row);
+    }
+    head
+}
+
+/// Row structure of a chunk that starts at a line start.
 #[cfg(feature = "parallel")]
-fn decode_bytes_parallel<'a>(input: &'a [u8]) -> Vec<Vec<Cow<'a, [u8]>>> {
-    let num_threads = rayon::current_num_threads();
-    let chunk_size = input.len() / num_threads;
+#[derive(Debug, Clone, Copy, Default)]
+struct ChunkShape {
+    /// Row terminators (empty lines).
+    ends: usize,
+    /// Cells before the first row end (all cells when `ends == 0`).
+    head: usize,
+    /// Cells after the last row end.
+    tail: usize,
+}
 
-    if chunk_size == 0 {
-        return decode_bytes_sequential(input);
+#[cfg(feature = "parallel")]
+impl ChunkShape {
+    /// Shape of `chunk`, from branch-free byte counts: row ends are counted
+    /// rather than visited, and cells only before the first and after the
+    /// last row end.
+    fn of(chunk: &[u8]) -> Self {
+        // A row end is an LF at a line start: at 0, or right after another LF.
+        let leading = chunk.first() == Some(&b'\n');
+        let ends = usize::from(leading) + count_lf_pairs(chunk);
+
+        // Cells before the first / after the last row end: one per LF, plus
+        // an unterminated last line.
+        let unterminated = usize::from(chunk.last().is_some_and(|&b| b != b'\n'));
+        if ends == 0 {
+            return ChunkShape { ends, head: count_lf(chunk) + unterminated, tail: 0 };
+        }
+        let first_end = if leading {
+            0
+        } else {
+            memmem::find(chunk, b"\n\n").map_or(0, |at| at + 1)
+        };
+        let last_end = memmem::rfind(chunk, b"\n\n").map_or(0, |at| at + 1);
+
+        ChunkShape {
+            ends,
+            head: count_lf(&chunk[..first_end]),
+            tail: count_lf(&chunk[last_end + 1..]) + unterminated,
+        }
     }
+}
+
+/// Number of LF bytes in `s`.
+#[cfg(feature = "parallel")]
+fn count_lf(s: &[u8]) -> usize {
+    // Per-block `u8` sums (a block can't overflow one) vectorize well.
+    s.chunks(u8::MAX as usize)
+        .map(|block| usize::from(block.iter().fold(0u8, |n, &b| n + u8::from(b == b'\n'))))
+        .sum()
+}
+
+/// Number of LF bytes in `s` directly preceded by another LF.
+fn count_lf_pairs(s: &[u8]) -> usize {
+    let Some((_, rest)) = s.split_first() else {
+        return 0;
+    };
+    rest.chunks(u8::MAX as usize)
+        .zip(s.chunks(u8::MAX as usize))
+        .map(|(block, prev)| {
+            let pairs = block.iter().zip(prev).fold(0u8, |n, (&b, &p)| n + u8::from(b == b'\n' && p == b'\n'));
+            usize::from(pairs)
+        })
+        .sum()
+}
 
-    // Find N-1 split points at \n\n boundaries near evenly-spaced positions.
-    // Cost: O(N * avg_row_len) — negligible compared to input size.
-    let finder = memmem::Finder::new(b"\n\n");
-    let mut splits = Vec::with_capacity(num_threads + 1);
+/// Split points at line starts near `num_chunks` evenly-spaced positions,
+/// including `0` and `input.len()`.
+#[cfg(feature = "parallel")]
+fn line_splits(input: &[u8], num_chunks: usize) -> Vec<usize> {
+    let chunk_size = input.len() / num_chunks.max(1);
+    let mut splits = Vec::with_capacity(num_chunks + 1);
     splits.push(0usize);
 
-    for i in 1..num_threads {
-        let nominal = i * chunk_size;
-        if let Some(offset) = finder.find(&input[nominal..]) {
-            let split = nominal + offset + 2; // byte after \n\n
-            if split < input.len() {
-                splits.push(split);
+    if chunk_size > 0 {
+        for i in 1..num_chunks {
+            let nominal = i * chunk_size;
+            if let Some(offset) = memchr::memchr(b'\n', &input[nominal..]) {
+                let split = nominal + offset + 1; // byte after \n
+                if split < input.len() {
+                    splits.push(split);
+                }
             }
         }
     }
     splits.push(input.len());
     splits.dedup();
+    splits
+}
+
+/// [`decode_rows`], reporting to `tracker` and stopping when it is cancelled.
+///
+/// Tracked decodes go row-aligned piece by piece, so a cancelled decode stops
+/// within about [`TRACKING_INTERVAL`] bytes.
+fn decode_rows_tracked<'a, S: RowSink<'a>>(sink: &S, input: &'a [u8], tracker: &Tracker) -> Result<Vec<S::Row>, Cancelled> {
+    if !tracker.is_active() {
+        return Ok(decode_rows(sink, input));
+    }
+
+    let mut rows = Vec::new();
+    let mut start = 0;
+    while start < input.len() {
+        tracker.check()?;
+        let end = boundaries::next_row_start(input, start + TRACKING_INTERVAL);
+        let before = rows.len();
+        decode_chunk(sink, &input[start..end], 0, |row| rows.push(row));
+        tracker.advance(end - start, rows.len() - before);
+        start = end;
+    }
+    Ok(rows)
+}
+
+/// Decode `input` in parallel, with the chunk count picked from the pool size.
+#[cfg(feature = "parallel")]
+fn decode_rows_parallel<'a, S: RowSink<'a>>(sink: &S, input: &'a [u8], tracker: &Tracker) -> Result<Vec<S::Row>, Cancelled> {
+    let num_threads = rayon::current_num_threads();
+    if num_threads == 1 {
+        return decode_rows_tracked(sink, input, tracker);
+    }
+    let max_chunks = num_threads * CHUNKS_PER_THREAD;
+    let mut num_chunks = (input.len() / MIN_CHUNK_SIZE).clamp(1, max_chunks);
+    if tracker.is_active() {
+        num_chunks = num_chunks.max(input.len() / TRACKING_INTERVAL);
+    }
+    decode_rows_chunked(sink, input, num_chunks, tracker)
+}
 
+/// Decode `input` as (up to) `num_chunks` chunks in parallel.
+#[cfg(feature = "parallel")]
+fn decode_rows_chunked<'a, S: RowSink<'a>>(
+    sink: &S,
+    input: &'a [u8],
+    num_chunks: usize,
+    tracker: &Tracker,
+) -> Result<Vec<S::Row>, Cancelled> {
+    let splits = line_splits(input, num_chunks);
     if splits.len() <= 2 {
-        return decode_bytes_sequential(input);
+        return decode_rows_tracked(sink, input, tracker);
+    }
+    let chunks: Vec<&'a [u8]> = splits.windows(2).map(|w| &input[w[0]..w[1]]).collect();
+
+    // Phase 1: shapes, then each chunk's first row index and first column.
+    let shapes: Vec<ChunkShape> = chunks.par_iter().map(|chunk| ChunkShape::of(chunk)).collect();
+
+    let mut starts = Vec::with_capacity(chunks.len());
+    let (mut row, mut col) = (0, 0);
+    for shape in &shapes {
+        starts.push((row, col));
+        if shape.ends == 0 {
+            col += shape.head;
+        } else {
+            row += shape.ends;
+            col = shape.tail;
+        }
     }
+    let total_rows = row + usize::from(col > 0);
+    tracker.check()?;
+
+    // Phase 2: decode every chunk into its own slice of the result. A chunk
+    // owns the rows that start inside it: all of its rows except the first,
+    // when it starts mid-row.
+    let mut rows: Vec<S::Row> = Vec::with_capacity(total_rows);
+    rows.par_extend((0..total_rows).into_par_iter().map(|_| S::Row::default()));
 
-    // Parse each chunk in parallel. Each chunk starts at a row boundary
-    // (or byte 0), so the sequential parser produces correct results per chunk.
-    let chunks: Vec<&[u8]> = splits.windows(2).map(|w| &input[w[0]..w[1]]).collect();
+    let owned_from = |&(row, col): &(usize, usize)| row + usize::from(col > 0);
+    let mut slots = Vec::with_capacity(chunks.len());
+    let mut remaining = &mut rows[..];
+    for (i, start) in starts.iter().enumerate() {
+        let end = starts.get(i + 1).map_or(total_rows, owned_from);
+        let (slot, rest) = remaining.split_at_mut(end - owned_from(start));
+        slots.push(slot);
+        remaining = rest;
+    }
 
-    let chunk_results: Vec<Vec<Vec<Cow<'a, [u8]>>>> = chunks
+    let heads: Vec<Option<S::Row>> = chunks
         .par_iter()
-        .map(|chunk| decode_bytes_sequential(chunk))
+        .zip(slots)
+        .zip(&starts)
+        .map(|((chunk, slot), &(_, first_col))| {
+            if tracker.check().is_err() {
+                return None;
+            }
+            let owned = slot.len();
+            let mut slot = slot.iter_mut();
+            let head = decode_chunk(sink, chunk, first_col, |row| {
+                *slot.next().expect("chunk shape undercounted rows") = row;
+            });
+            debug_assert!(slot.next().is_none(), "chunk shape overcounted rows");
+            tracker.advance(chunk.len(), owned);
+            head
+        })
         .collect();
+    tracker.check()?;
 
-    let total_rows: usize = chunk_results.iter().map(|r| r.len()).sum();
-    let mut result = Vec::with_capacity(total_rows);
-    for chunk_rows in chunk_results {
-        result.extend(chunk_rows);
+    for (&(row, first_col), head) in starts.iter().zip(heads) {
+        if let Some(head) = head {
+            sink.join(&mut rows[row], head, first_col);
+        }
     }
-    result
+
+    Ok(rows)
 }
 
 /// Unescape a single NSV cell.
@@ -172,32 +816,57 @@ pub fn unescape_bytes(s: &[u8]) -> Cow<'_, [u8]> {
         return Cow::Owned(Vec::new());
     }
 
-    if !s.contains(&b'\\') {
-        return Cow::Borrowed(s);
+    match memchr::memchr(b'\\', s) {
+        None => Cow::Borrowed(s),
+        Some(first) => {
+            let mut out = Vec::with_capacity(s.len());
+            unescape_from(s, first, &mut out);
+            Cow::Owned(out)
+        }
     }
+}
 
-    let mut out = Vec::with_capacity(s.len());
-    let mut escaped = false;
+/// Unescape a single raw cell, appending the result to `out`.
+///
+/// Same rules as [`unescape_bytes`]; nothing is allocated beyond growing `out`.
+pub fn unescape_into(s: &[u8], out: &mut Vec<u8>) {
+    if s == b"\\" {
+        return;
+    }
 
-    for &b in s {
-        if escaped {
-            match b {
-                b'n' => out.push(b'\n'),
-                b'\\' => out.push(b'\\'),
-                _ => {
-                    out.push(b'\\');
-                    out.push(b);
-                }
+    match memchr::memchr(b'\\', s) {
+        None => out.extend_from_slice(s),
+        Some(first) => unescape_from(s, first, out),
+    }
+}
+
+/// Unescape `s` into `out`, given the position of its first backslash.
+///
+/// Runs between backslashes are located with `memchr` and copied in bulk.
+fn unescape_from(s: &[u8], first: usize, out: &mut Vec<u8>) {
+    let mut start = 0;
+    let mut pos = first;
+
+    loop {
+        out.extend_from_slice(&s[start..pos]);
+        match s.get(pos + 1) {
+            Some(b'n') => out.push(b'\n'),
+            Some(b'\\') => out.push(b'\\'),
+            Some(&b) => {
+                out.push(b'\\');
+                out.push(b);
             }
-            escaped = false;
-        } else if b == b'\\' {
-            escaped = true;
-        } else {
-            out.push(b);
+            // Dangling backslash: stripped.
+            None => return,
+        }
+        start = pos + 2;
+        match memchr::memchr(b'\\', &s[start..]) {
+            Some(offset) => pos = start + offset,
+            None => break,
         }
     }
 
-    Cow::Owned(out)
+    out.extend_from_slice(&s[start..]);
 }
 
 /// Escape a single NSV cell.
@@ -224,25 +893,50 @@ pub fn escape_bytes(s: &[u8]) -> Cow<'_, [u8]> {
         return Cow::Owned(b"\\".to_vec());
     }
 
-    if s.contains(&b'\n') || s.contains(&b'\\') {
-        let mut out = Vec::with_capacity(s.len() + s.len() / 4);
-        for &b in s {
-            match b {
-                b'\\' => {
-                    out.push(b'\\');
-                    out.push(b'\\');
-                }
-                b'\n' => {
-                    out.push(b'\\');
-                    out.push(b'n');
-                }
-                _ => out.push(b),
-            }
+    match memchr::memchr2(b'\n', b'\\', s) {
+        None => Cow::Borrowed(s),
+        Some(first) => {
+            let mut out = Vec::with_capacity(s.len() + s.len() / 4);
+            escape_from(s, first, &mut out);
+            Cow::Owned(out)
+        }
+    }
+}
+
+/// Escape a single raw cell, appending the result to `out`.
+///
+/// Same rules as [`escape_bytes`]; nothing is allocated beyond growing `out`.
+pub fn escape_into(s: &[u8], out: &mut Vec<u8>) {
+    if s.is_empty() {
+        out.push(b'\\');
+        return;
+    }
+
+    match memchr::memchr2(b'\n', b'\\', s) {
+        None => out.extend_from_slice(s),
+        Some(first) => escape_from(s, first, out),
+    }
+}
+
+/// Escape `s` into `out`, given the position of its first LF or backslash.
+///
+/// Runs between special bytes are located with `memchr2` and copied in bulk.
+fn escape_from(s: &[u8], first: usize, out: &mut Vec<u8>) {
+    let mut start = 0;
+    let mut pos = first;
+
+    loop {
+        out.extend_from_slice(&s[start..pos]);
+        out.push(b'\\');
+        out.push(if s[pos] == b'\n' { b'n' } else { b'\\' });
+        start = pos + 1;
+        match memchr::memchr2(b'\n', b'\\', &s[start..]) {
+            Some(offset) => pos = start + offset,
+            None => break,
         }
-        Cow::Owned(out)
-    } else {
-        Cow::Borrowed(s)
     }
+
+    out.extend_from_slice(&s[start..]);
 }
 
 // ── Projected (column-selective) parsing ─────────────────────────────
@@ -251,650 +945,2503 @@ pub fn escape_bytes(s: &[u8]) -> Cow<'_, [u8]> {
 // columns entirely (no allocation, no unescape), and directly produces
 // the final `Vec<Vec<Vec<u8>>>`.
 
-/// Build a column-map: `col_map[original_col] = projected_index`.
-/// Entries for non-projected columns are `usize::MAX`.
-fn build_col_map(columns: &[usize]) -> (Vec<usize>, usize) {
-    let max_col = columns.iter().copied().max().unwrap_or(0);
-    let mut col_map = vec![usize::MAX; max_col + 1];
-    for (proj_idx, &orig_col) in columns.iter().enumerate() {
-        col_map[orig_col] = proj_idx;
-    }
-    (col_map, max_col)
+/// Only the cells of `columns`, in that order; cells past the end of a row stay empty.
+///
+/// Slots are looked up in a direct table for the low columns, and by binary
+/// search past it. The table is capped in proportion to the number of columns
+/// projected, so lookups never cost memory in proportion to their indices.
+struct Projection<'c> {
+    columns: &'c [usize],
+    /// `dense[col]` is the projected index of `col`, `usize::MAX` if none.
+    dense: Vec<usize>,
+    /// `(original_col, projected_index)` for columns past `dense`, sorted.
+    sparse: Vec<(usize, usize)>,
+    max_col: usize,
 }
 
-/// Decode only the specified columns from raw bytes.
-///
-/// Single-pass: scans for cell/row boundaries and directly unescapes
-/// only the cells in projected columns.  No intermediate structural index.
-/// Each inner vec has exactly `columns.len()` entries (same order as `columns`).
-///
-/// Cells are returned as `Cow<[u8]>` — borrowed when no unescaping was needed.
-pub fn decode_bytes_projected<'a>(input: &'a [u8], columns: &[usize]) -> Vec<Vec<Cow<'a, [u8]>>> {
-    if input.is_empty() || columns.is_empty() {
-        return Vec::new();
+impl<'c> Projection<'c> {
+    fn new(columns: &'c [usize]) -> Self {
+        let max_col = columns.iter().copied().max().unwrap_or(0);
+        let dense_len = max_col.saturating_add(1).min(64 + 8 * columns.len());
+        let mut dense = vec![usize::MAX; dense_len];
+        let mut sparse = Vec::new();
+        // A repeated column fills its last slot
+        for (proj_idx, &orig_col) in columns.iter().enumerate() {
+            match dense.get_mut(orig_col) {
+                Some(slot) => *slot = proj_idx,
+                None => sparse.push((orig_col, proj_idx)),
+            }
+        }
+        sparse.sort_by_key(|&(orig_col, _)| orig_col);
+        sparse.reverse();
+        sparse.dedup_by_key(|&mut (orig_col, _)| orig_col);
+        sparse.reverse();
+        Projection { columns, dense, sparse, max_col }
+    }
+
+    /// Projected index of original column `col`, if it is projected.
+    #[inline]
+    fn slot(&self, col: usize) -> Option<usize> {
+        match self.dense.get(col) {
+            Some(&slot) => (slot != usize::MAX).then_some(slot),
+            None if col > self.max_col => None,
+            None => self.sparse.binary_search_by_key(&col, |&(orig_col, _)| orig_col).ok().map(|i| self.sparse[i].1),
+        }
     }
+}
 
-    #[cfg(feature = "parallel")]
-    if input.len() >= PARALLEL_THRESHOLD {
-        return decode_projected_parallel(input, columns);
-    }
+impl<'a> RowSink<'a> for Projection<'_> {
+    type Row = Vec<Cow<'a, [u8]>>;
 
-    decode_projected_sequential(input, columns)
-}
+    fn row(&self) -> Self::Row {
+        vec![Cow::Borrowed(b""); self.columns.len()]
+    }
 
-/// Sequential single-pass projected decode.
-fn decode_projected_sequential<'a>(input: &'a [u8], columns: &[usize]) -> Vec<Vec<Cow<'a, [u8]>>> {
-    let (col_map, max_col) = build_col_map(columns);
-    let stride = columns.len();
-    let mut data: Vec<Vec<Cow<'a, [u8]>>> = Vec::new();
-    let mut row: Vec<Cow<'a, [u8]>> = vec![Cow::Borrowed(b""); stride];
-    let mut col_idx: usize = 0;
-    let mut start = 0;
-    let mut row_has_cells = false;
-
-    for (pos, &b) in input.iter().enumerate() {
-        if b == b'\n' {
-            if pos > start {
-                if col_idx <= max_col {
-                    if let Some(&proj_idx) = col_map.get(col_idx) {
-                        if proj_idx != usize::MAX {
-                            row[proj_idx] = unescape_bytes(&input[start..pos]);
-                        }
-                    }
-                }
-                col_idx += 1;
-                row_has_cells = true;
-            } else {
-                if row_has_cells || !data.is_empty() || col_idx == 0 {
-                    data.push(row);
-                    row = vec![Cow::Borrowed(b""); stride];
-                }
-                col_idx = 0;
-                row_has_cells = false;
-            }
-            start = pos + 1;
+    #[inline]
+    fn push(&self, row: &mut Self::Row, col: usize, raw: &'a [u8], escaped: bool) {
+        if let Some(slot) = self.slot(col) {
+            row[slot] = cell(raw, escaped);
         }
     }
 
-    if start < input.len() {
-        if col_idx <= max_col {
-            if let Some(&proj_idx) = col_map.get(col_idx) {
-                if proj_idx != usize::MAX {
-                    row[proj_idx] = unescape_bytes(&input[start..]);
-                }
+    fn join(&self, row: &mut Self::Row, mut rest: Self::Row, first_col: usize) {
+        for (slot, &col) in self.columns.iter().enumerate() {
+            if col >= first_col {
+                row[slot] = std::mem::take(&mut rest[slot]);
             }
         }
-        row_has_cells = true;
     }
+}
+
+/// Decode only the specified columns from raw bytes.
+///
+/// Single-pass: scans for cell/row boundaries and directly unescapes
+/// only the cells in projected columns.
+/// Each inner vec has exactly `columns.len()` entries (same order as `columns`),
+/// and every input row yields one, even with no columns at all.
+/// `columns` can also be a [`ColumnSelector`], decoded as by
+/// [`decode_bytes_selected`]; see [`Columns`].
+///
+/// Cells are returned as `Cow<[u8]>` — borrowed when no unescaping was needed.
+pub fn decode_bytes_projected<'a>(input: &'a [u8], columns: impl Columns) -> Vec<Vec<Cow<'a, [u8]>>> {
+    decode_bytes_projected_with(input, columns, &DecodeOptions::default()).expect("no cancel token")
+}
 
-    if row_has_cells {
-        data.push(row);
+/// [`decode_bytes_projected`] with explicit [`DecodeOptions`].
+///
+/// Fails only when the options' [`CancelToken`] is cancelled mid-decode.
+pub fn decode_bytes_projected_with<'a>(
+    input: &'a [u8],
+    columns: impl Columns,
+    options: &DecodeOptions,
+) -> Result<Vec<Vec<Cow<'a, [u8]>>>, Cancelled> {
+    let Some(columns) = columns.static_columns() else {
+        return decode_bytes_selected_with(input, &columns.to_selector(), options);
+    };
+    if input.is_empty() {
+        return Ok(Vec::new());
     }
 
-    data
+    options.decode_rows(&Projection::new(&columns), input)
 }
 
-/// Parallel single-pass projected decode.
-#[cfg(feature = "parallel")]
-fn decode_projected_parallel<'a>(input: &'a [u8], columns: &[usize]) -> Vec<Vec<Cow<'a, [u8]>>> {
-    let num_threads = rayon::current_num_threads();
-    let chunk_size = input.len() / num_threads;
-
-    if chunk_size == 0 {
-        return decode_projected_sequential(input, columns);
-    }
+/// Raw cells, left escaped until a [`ColumnSelector`] has seen the row's width.
+struct RawCells;
 
-    let finder = memmem::Finder::new(b"\n\n");
-    let mut splits = Vec::with_capacity(num_threads + 1);
-    splits.push(0usize);
+impl<'a> RowSink<'a> for RawCells {
+    type Row = Vec<(&'a [u8], bool)>;
 
-    for i in 1..num_threads {
-        let nominal = i * chunk_size;
-        if let Some(offset) = finder.find(&input[nominal..]) {
-            let split = nominal + offset + 2;
-            if split < input.len() {
-                splits.push(split);
-            }
-        }
+    fn row(&self) -> Self::Row {
+        Vec::new()
     }
-    splits.push(input.len());
-    splits.dedup();
 
-    if splits.len() <= 2 {
-        return decode_projected_sequential(input, columns);
+    #[inline]
+    fn push(&self, row: &mut Self::Row, _col: usize, raw: &'a [u8], escaped: bool) {
+        row.push((raw, escaped));
     }
 
-    let chunks: Vec<&[u8]> = splits.windows(2).map(|w| &input[w[0]..w[1]]).collect();
+    fn join(&self, row: &mut Self::Row, rest: Self::Row, _first_col: usize) {
+        row.extend(rest);
+    }
+}
 
-    let chunk_results: Vec<Vec<Vec<Cow<'a, [u8]>>>> = chunks
-        .par_iter()
-        .map(|chunk| decode_projected_sequential(chunk, columns))
-        .collect();
+/// Decode the columns picked by `selector`. See [`ColumnSelector`].
+///
+/// Selectors that don't depend on row width run as [`decode_bytes_projected`].
+/// Others first split each row into raw cells, then unescape only the selected
+/// ones; cells missing from a row come back empty. Every input row yields a
+/// row, even when nothing in it is selected.
+///
+/// ```
+/// use nsv::{decode_bytes_selected, ColumnSelector};
+///
+/// let input = b"a\nb\nc\n\nd\ne\n\n";
+/// let last = decode_bytes_selected(input, &ColumnSelector::new().index(-1));
+/// assert_eq!(last, [[&b"c"[..]], [b"e"]]);
+/// ```
+pub fn decode_bytes_selected<'a>(input: &'a [u8], selector: &ColumnSelector) -> Vec<Vec<Cow<'a, [u8]>>> {
+    decode_bytes_selected_with(input, selector, &DecodeOptions::default()).expect("no cancel token")
+}
 
-    let total_rows: usize = chunk_results.iter().map(|r| r.len()).sum();
-    let mut result = Vec::with_capacity(total_rows);
-    for chunk_rows in chunk_results {
-        result.extend(chunk_rows);
+/// [`decode_bytes_selected`] with explicit [`DecodeOptions`].
+///
+/// Fails only when the options' [`CancelToken`] is cancelled mid-decode.
+pub fn decode_bytes_selected_with<'a>(
+    input: &'a [u8],
+    selector: &ColumnSelector,
+    options: &DecodeOptions,
+) -> Result<Vec<Vec<Cow<'a, [u8]>>>, Cancelled> {
+    if input.is_empty() {
+        return Ok(Vec::new());
     }
-    result
+    if let Some(columns) = selector.static_columns() {
+        return options.decode_rows(&Projection::new(&columns), input);
+    }
+
+    let rows = options.decode_rows(&RawCells, input)?;
+    Ok(options.map_rows(input.len(), rows, |row| {
+        let mut selected = Vec::new();
+        selector.for_each(row.len(), |col| {
+            selected.push(col.map_or(Cow::Borrowed(&b""[..]), |col| cell(row[col].0, row[col].1)));
+        });
+        selected
+    }))
 }
 
-/// Encode a seqseq into an NSV string.
-pub fn encode(data: &[Vec<String>]) -> String {
-    let mut result = Vec::new();
+/// A projected row that tells missing cells from empty ones.
+/// See [`decode_bytes_projected_ragged`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProjectedRow<'a> {
+    /// One entry per projected column: `None` when the row has no such column.
+    pub cells: Vec<Option<Cow<'a, [u8]>>>,
+    /// Number of cells in the source row.
+    pub width: usize,
+}
 
-    for row in data {
-        for cell in row {
-            result.extend_from_slice(&escape_bytes(cell.as_bytes()));
-            result.push(b'\n');
-        }
-        result.push(b'\n');
-    }
+/// [`Projection`] into [`ProjectedRow`]s.
+struct RaggedProjection<'c>(Projection<'c>);
 
-    // Safety: encoding only inserts ASCII bytes (\, n, LF) — preserves UTF-8.
-    String::from_utf8(result).unwrap()
-}
+impl<'a> RowSink<'a> for RaggedProjection<'_> {
+    type Row = ProjectedRow<'a>;
 
-/// Encode a seqseq of byte vectors into raw NSV bytes.
-pub fn encode_bytes(data: &[Vec<Vec<u8>>]) -> Vec<u8> {
-    let mut result = Vec::new();
+    fn row(&self) -> Self::Row {
+        ProjectedRow { cells: vec![None; self.0.columns.len()], width: 0 }
+    }
 
-    for row in data {
-        for cell in row {
-            result.extend_from_slice(&escape_bytes(cell));
-            result.push(b'\n');
+    #[inline]
+    fn push(&self, row: &mut Self::Row, col: usize, raw: &'a [u8], escaped: bool) {
+        if let Some(slot) = self.0.slot(col) {
+            row.cells[slot] = Some(cell(raw, escaped));
         }
-        result.push(b'\n');
+        row.width = col + 1;
     }
 
-    result
+    fn join(&self, row: &mut Self::Row, mut rest: Self::Row, first_col: usize) {
+        for (slot, &col) in self.0.columns.iter().enumerate() {
+            if col >= first_col {
+                row.cells[slot] = rest.cells[slot].take();
+            }
+        }
+        row.width = row.width.max(rest.width);
+    }
 }
 
-/// A single warning found during validation.
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct Warning {
-    pub kind: WarningKind,
-    pub pos: usize,
-    pub line: usize,
-    pub col: usize,
+/// Like [`decode_bytes_projected`], but a projected column past the end of a
+/// row comes back as `None` rather than as an empty cell, and each row carries
+/// its actual cell count.
+///
+/// ```
+/// use std::borrow::Cow;
+///
+/// let rows = nsv::decode_bytes_projected_ragged(b"a\n\\\n\nb\n\n", &[1]);
+/// assert_eq!(rows[0].cells, [Some(Cow::Borrowed(&b""[..]))]); // a real empty cell
+/// assert_eq!(rows[1].cells, [None]);                          // no column 1
+/// assert_eq!((rows[0].width, rows[1].width), (2, 1));
+/// ```
+pub fn decode_bytes_projected_ragged<'a>(input: &'a [u8], columns: impl Columns) -> Vec<ProjectedRow<'a>> {
+    decode_bytes_projected_ragged_with(input, columns, &DecodeOptions::default()).expect("no cancel token")
 }
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub enum WarningKind {
-    /// `\` followed by a byte other than `n` or `\`
-    UnknownEscape(u8),
-    /// `\` immediately before LF or at EOF
-    DanglingBackslash,
-    /// Non-empty input not ending with LF
-    NoTerminalLf,
+/// [`decode_bytes_projected_ragged`] with explicit [`DecodeOptions`].
+///
+/// Fails only when the options' [`CancelToken`] is cancelled mid-decode.
+pub fn decode_bytes_projected_ragged_with<'a>(
+    input: &'a [u8],
+    columns: impl Columns,
+    options: &DecodeOptions,
+) -> Result<Vec<ProjectedRow<'a>>, Cancelled> {
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+    if let Some(columns) = columns.static_columns() {
+        return options.decode_rows(&RaggedProjection(Projection::new(&columns)), input);
+    }
+
+    let selector = columns.to_selector();
+    let rows = options.decode_rows(&RawCells, input)?;
+    Ok(options.map_rows(input.len(), rows, |row| {
+        let mut cells = Vec::new();
+        selector.for_each(row.len(), |col| cells.push(col.map(|col| cell(row[col].0, row[col].1))));
+        ProjectedRow { cells, width: row.len() }
+    }))
 }
 
-/// Report edge cases in raw NSV input without altering parsing behavior.
+/// Decode the `columns` of the rows whose `keys` cells satisfy `predicate`.
 ///
-/// Warns on unknown escape sequences, dangling backslashes, and missing terminal LF.
-/// Positions are byte offsets; line and col are 1-indexed.
-pub fn check(input: &[u8]) -> Vec<Warning> {
+/// `predicate` gets the unescaped cells of `keys`, in that order (empty for
+/// cells past the end of a row). Only rows it accepts have their projected
+/// cells unescaped and collected; rejected rows cost a boundary scan and the
+/// key cells. Output rows are as in [`decode_bytes_projected`], in input
+/// order, and `columns` can be a [`ColumnSelector`] too. Runs in parallel above [`PARALLEL_THRESHOLD`], so `predicate` must
+/// be `Sync`.
+///
+/// ```
+/// let input = b"alice\n30\nparis\n\nbob\n25\nrome\n\ncarol\n41\nparis\n\n";
+/// let rows = nsv::decode_bytes_filtered(input, &[0, 1], &[2], |keys| keys[0] == &b"paris"[..]);
+/// assert_eq!(rows, [[&b"alice"[..], b"30"], [b"carol", b"41"]]);
+/// ```
+pub fn decode_bytes_filtered<'a, P>(input: &'a [u8], columns: impl Columns, keys: &[usize], predicate: P) -> Vec<Vec<Cow<'a, [u8]>>>
+where
+    P: Fn(&[Cow<'a, [u8]>]) -> bool + Sync,
+{
     if input.is_empty() {
         return Vec::new();
     }
+    let selector;
+    let listed = columns.static_columns();
+    let kept = match &listed {
+        Some(columns) => Kept::Projected(Projection::new(columns)),
+        None => {
+            selector = columns.to_selector();
+            Kept::Selected(&selector)
+        }
+    };
 
-    let mut warnings = Vec::new();
-    let len = input.len();
-    let mut line: usize = 1;
-    let mut line_start: usize = 0;
-    let mut escaped = false;
+    #[cfg(feature = "parallel")]
+    if input.len() >= PARALLEL_THRESHOLD {
+        let points = boundaries::split_points(input, row_chunk_count(input.len()));
+        let chunks: Vec<Vec<_>> = points
+            .par_windows(2)
+            .map(|w| filter_chunk(&kept, keys, &predicate, &input[w[0]..w[1]]))
+            .collect();
+        return chunks.into_iter().flatten().collect();
+    }
 
-    for (i, &b) in input.iter().enumerate() {
-        if escaped {
-            match b {
-                b'n' | b'\\' => {}
-                b'\n' if i - 1 != line_start => warnings.push(Warning {
-                    kind: WarningKind::DanglingBackslash,
-                    pos: i - 1,
-                    line,
-                    col: i - line_start,
-                }),
-                b'\n' => {}
-                _ => warnings.push(Warning {
-                    kind: WarningKind::UnknownEscape(b),
-                    pos: i - 1,
-                    line,
-                    col: i - line_start,
-                }),
+    filter_chunk(&kept, keys, &predicate, input)
+}
+
+/// The columns [`decode_bytes_filtered`] keeps.
+enum Kept<'c> {
+    Projected(Projection<'c>),
+    /// A selector that depends on each row's width.
+    Selected(&'c ColumnSelector),
+}
+
+/// The filtered, projected rows of `chunk`, which starts at a row start.
+fn filter_chunk<'a, P>(kept: &Kept, keys: &[usize], predicate: &P, chunk: &'a [u8]) -> Vec<Vec<Cow<'a, [u8]>>>
+where
+    P: Fn(&[Cow<'a, [u8]>]) -> bool,
+{
+    // Cells past both the projection and the keys are never looked at.
+    let key_width = keys.iter().copied().max().map_or(0, |k| k.saturating_add(1));
+    let width = match kept {
+        Kept::Projected(projection) => key_width.max(projection.max_col.saturating_add(1)),
+        Kept::Selected(_) => usize::MAX,
+    };
+    let mut out = Vec::new();
+    let mut cells: Vec<(&'a [u8], bool)> = Vec::new();
+    let mut key_cells: Vec<Cow<'a, [u8]>> = Vec::with_capacity(keys.len());
+    let mut col = 0;
+
+    let mut end_row = |cells: &mut Vec<(&'a [u8], bool)>| {
+        key_cells.clear();
+        key_cells.extend(keys.iter().map(|&k| cells.get(k).map_or(Cow::Borrowed(&b""[..]), |&(raw, escaped)| cell(raw, escaped))));
+        if predicate(&key_cells) {
+            // Escaped key cells are unescaped already: reuse them
+            let value = |col: usize| {
+                let (raw, escaped) = cells[col];
+                match keys.iter().position(|&k| k == col) {
+                    Some(i) if escaped => key_cells[i].clone(),
+                    _ => cell(raw, escaped),
+                }
+            };
+            let row = match kept {
+                Kept::Projected(projection) => {
+                    let mut row = RowSink::row(projection);
+                    for col in 0..cells.len() {
+                        if let Some(slot) = projection.slot(col) {
+                            row[slot] = value(col);
+                        }
+                    }
+                    row
+                }
+                Kept::Selected(selector) => {
+                    let mut row = Vec::new();
+                    selector.for_each(cells.len(), |col| row.push(col.map_or(Cow::Borrowed(&b""[..]), value)));
+                    row
+                }
+            };
+            out.push(row);
+        }
+        cells.clear();
+    };
+
+    let (rest, escaped) = scan_lines(chunk, |start, end, escaped| {
+        if end > start {
+            if col < width {
+                cells.push((&chunk[start..end], escaped));
             }
-            escaped = false;
-        } else if b == b'\\' {
-            escaped = true;
+            col += 1;
+        } else {
+            end_row(&mut cells);
+            col = 0;
         }
-        if b == b'\n' {
-            line += 1;
-            line_start = i + 1;
+    });
+    if rest < chunk.len() {
+        if col < width {
+            cells.push((&chunk[rest..], escaped));
         }
+        col += 1;
     }
-
-    if escaped {
-        warnings.push(Warning {
-            kind: WarningKind::DanglingBackslash,
-            pos: len - 1,
-            line,
-            col: len - line_start,
-        });
+    if col > 0 {
+        end_row(&mut cells);
     }
+    out
+}
 
-    if line_start != len {
-        warnings.push(Warning {
-            kind: WarningKind::NoTerminalLf,
-            pos: len,
-            line,
-            col: len - line_start + 1,
-        });
-    }
+// ── Source spans ─────────────────────────────────────────────────────
 
-    warnings
+/// A decoded cell with its source location. See [`decode_bytes_with_spans`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpannedCell<'a> {
+    pub value: Cow<'a, [u8]>,
+    /// The cell's raw bytes in the input, escapes included, without its LF.
+    pub span: Range<usize>,
+    /// 1-based line of the cell, counted as in [`check`].
+    pub line: usize,
 }
 
-// ── Streaming ────────────────────────────────────────────────────────
+/// Cells with their spans. `line` holds the cell's column until the rows are
+/// numbered after the decode: every line is either a cell or a row's empty
+/// terminating line, so row `r` starts on line `1 + Σ (width + 1)` over the
+/// rows before it.
+struct Spans<'a, P> {
+    input: &'a [u8],
+    projection: P,
+}
 
-/// Streaming NSV reader. Yields one complete row of byte vectors at a time.
-///
-/// On EOF, returns `Ok(None)` without discarding buffered state — calling
-/// `next_row()` again after more data arrives resumes where it left off.
-pub struct Reader<R> {
-    inner: io::BufReader<R>,
-    line_buf: Vec<u8>,
-    row: Vec<Vec<u8>>,
+impl<'a, P> Spans<'a, P> {
+    #[inline]
+    fn cell(&self, col: usize, raw: &'a [u8], escaped: bool) -> SpannedCell<'a> {
+        // Cells are always slices of the input, so their offset is exact.
+        let start = raw.as_ptr() as usize - self.input.as_ptr() as usize;
+        SpannedCell { value: cell(raw, escaped), span: start..start + raw.len(), line: col }
+    }
 }
 
-impl<R: io::Read> Reader<R> {
-    pub fn new(reader: R) -> Self {
-        Self::from_buf_reader(io::BufReader::new(reader))
+impl<'a> RowSink<'a> for Spans<'a, ()> {
+    type Row = Vec<SpannedCell<'a>>;
+
+    fn row(&self) -> Self::Row {
+        Vec::new()
     }
 
-    pub fn from_buf_reader(reader: io::BufReader<R>) -> Self {
-        Reader { inner: reader, line_buf: Vec::new(), row: Vec::new() }
+    #[inline]
+    fn push(&self, row: &mut Self::Row, col: usize, raw: &'a [u8], escaped: bool) {
+        row.push(self.cell(col, raw, escaped));
     }
 
-    pub fn next_row(&mut self) -> io::Result<Option<Vec<Vec<u8>>>> {
-        let mut byte = [0u8; 1];
-        loop {
-            match self.inner.read(&mut byte) {
-                Ok(0) => return Ok(None),
-                Err(e) => return Err(e),
-                Ok(_) if byte[0] != b'\n' => self.line_buf.push(byte[0]),
-                Ok(_) if self.line_buf.is_empty() => return Ok(Some(std::mem::take(&mut self.row))),
-                Ok(_) => {
-                    self.row.push(unescape_bytes(&self.line_buf).into_owned());
-                    self.line_buf.clear();
-                }
-            }
+    fn join(&self, row: &mut Self::Row, rest: Self::Row, _first_col: usize) {
+        row.extend(rest);
+    }
+}
+
+impl<'a> RowSink<'a> for Spans<'a, Projection<'_>> {
+    /// The projected cells, and the row's width.
+    type Row = (Vec<Option<SpannedCell<'a>>>, usize);
+
+    fn row(&self) -> Self::Row {
+        (vec![None; self.projection.columns.len()], 0)
+    }
+
+    #[inline]
+    fn push(&self, row: &mut Self::Row, col: usize, raw: &'a [u8], escaped: bool) {
+        if let Some(slot) = self.projection.slot(col) {
+            row.0[slot] = Some(self.cell(col, raw, escaped));
         }
+        row.1 = col + 1;
     }
 
-    /// Completed cells of the row currently being assembled.
-    pub fn partial_row(&self) -> &[Vec<u8>] {
-        &self.row
+    fn join(&self, row: &mut Self::Row, mut rest: Self::Row, first_col: usize) {
+        for (slot, &col) in self.projection.columns.iter().enumerate() {
+            if col >= first_col {
+                row.0[slot] = rest.0[slot].take();
+            }
+        }
+        row.1 = row.1.max(rest.1);
     }
+}
 
-    /// Bytes accumulated for the cell currently being read (not yet unescaped).
-    pub fn partial_cell(&self) -> &[u8] {
-        &self.line_buf
+/// Decode `input` like [`decode_bytes`], keeping where each cell came from:
+/// the range of its raw bytes in `input` and its line number.
+///
+/// Spans cover the raw, escaped bytes, so they point at the same bytes as the
+/// positions in [`check`]'s warnings. Parallel above [`PARALLEL_THRESHOLD`].
+///
+/// ```
+/// let rows = nsv::decode_bytes_with_spans(b"a\nb\\nc\n\n\\\n\n");
+/// let cell = &rows[0][1];
+/// assert_eq!((&cell.value[..], cell.span.clone(), cell.line), (&b"b\nc"[..], 2..6, 2));
+/// assert_eq!((rows[1][0].span.clone(), rows[1][0].line), (8..9, 4));
+/// ```
+pub fn decode_bytes_with_spans(input: &[u8]) -> Vec<Vec<SpannedCell<'_>>> {
+    if input.is_empty() {
+        return Vec::new();
     }
 
-    /// Recover the inner `BufReader`.
-    pub fn into_inner(self) -> io::BufReader<R> {
-        self.inner
+    let sink = Spans { input, projection: () };
+    let mut rows = DecodeOptions::default().decode_rows(&sink, input).expect("no cancel token");
+    let mut first_line = 1;
+    for row in &mut rows {
+        for cell in row.iter_mut() {
+            cell.line += first_line;
+        }
+        first_line += row.len() + 1;
     }
+    rows
 }
 
-impl<R: io::Read> Iterator for Reader<R> {
-    type Item = io::Result<Vec<Vec<u8>>>;
-    fn next(&mut self) -> Option<Self::Item> {
-        self.next_row().transpose()
+/// [`decode_bytes_with_spans`] for only `columns`: one entry per projected
+/// column, `None` when the row has no such column, as in
+/// [`decode_bytes_projected_ragged`]. Rows are those of
+/// [`decode_bytes_projected`], so no columns still give one empty row per
+/// input row.
+pub fn decode_bytes_projected_with_spans<'a>(input: &'a [u8], columns: impl Columns) -> Vec<Vec<Option<SpannedCell<'a>>>> {
+    if input.is_empty() {
+        return Vec::new();
+    }
+    let Some(columns) = columns.static_columns() else {
+        return selected_with_spans(input, &columns.to_selector());
+    };
+
+    let sink = Spans { input, projection: Projection::new(&columns) };
+    let mut rows = DecodeOptions::default().decode_rows(&sink, input).expect("no cancel token");
+    let mut first_line = 1;
+    for (cells, width) in &mut rows {
+        for cell in cells.iter_mut().flatten() {
+            cell.line += first_line;
+        }
+        first_line += *width + 1;
     }
+    rows.into_iter().map(|(cells, _)| cells).collect()
 }
 
-/// Streaming NSV writer. Wraps any `W: Write` and writes one row at a time.
+/// [`decode_bytes_projected_with_spans`] for a selector that depends on each
+/// row's width.
+fn selected_with_spans<'a>(input: &'a [u8], selector: &ColumnSelector) -> Vec<Vec<Option<SpannedCell<'a>>>> {
+    let spans = Spans { input, projection: () };
+    let rows = DecodeOptions::default().decode_rows(&RawCells, input).expect("no cancel token");
+    let mut first_line = 1;
+    rows.into_iter()
+        .map(|row| {
+            let mut cells = Vec::new();
+            selector.for_each(row.len(), |col| cells.push(col.map(|col| spans.cell(first_line + col, row[col].0, row[col].1))));
+            first_line += row.len() + 1;
+            cells
+        })
+        .collect()
+}
+
+// ── Columnar decoding ────────────────────────────────────────────────
+
+/// Decode the columns picked by `selector`, column-major: one vector of cells
+/// per selected column, each holding one cell per row.
 ///
-/// No internal buffering — wrap the inner writer in `BufWriter` if needed.
-pub struct Writer<W> {
-    inner: W,
+/// Column `k` holds the `k`-th selected cell of every row. Rows ragged enough
+/// to yield fewer selected cells than others (a short row under an open
+/// range, say) are padded with empty cells, so every column is as long as the
+/// input has rows; as in [`decode_bytes_selected`], single indices past the end
+/// of a row yield empty cells too. Selectors that don't depend on row width
+/// always give their own number of columns, even for an empty input.
+///
+/// Runs in parallel above [`PARALLEL_THRESHOLD`]: each chunk fills its own
+/// column buffers, which are then concatenated.
+///
+/// ```
+/// use nsv::{decode_columns, ColumnSelector};
+///
+/// let input = b"alice\n30\n\nbob\n25\n\n";
+/// let columns = decode_columns(input, &ColumnSelector::new().index(1).index(0));
+/// assert_eq!(columns, [[&b"30"[..], b"25"], [b"alice", b"bob"]]);
+/// ```
+pub fn decode_columns<'a>(input: &'a [u8], selector: &ColumnSelector) -> Vec<Vec<Cow<'a, [u8]>>> {
+    let width = selector.static_columns().map_or(0, |columns| columns.len());
+
+    #[cfg(feature = "parallel")]
+    if input.len() >= PARALLEL_THRESHOLD {
+        let points = boundaries::split_points(input, row_chunk_count(input.len()));
+        let chunks: Vec<ColumnChunk> = points.par_windows(2).map(|w| columns_chunk(selector, width, &input[w[0]..w[1]])).collect();
+        return concat_columns(chunks, width);
+    }
+
+    columns_chunk(selector, width, input).columns
 }
 
-impl<W: Write> Writer<W> {
-    pub fn new(writer: W) -> Self {
-        Writer { inner: writer }
+/// Column buffers for the rows of one chunk.
+struct ColumnChunk<'a> {
+    columns: Vec<Vec<Cow<'a, [u8]>>>,
+    rows: usize,
+}
+
+/// The selected columns of `chunk`, which starts at a row start, padded to at
+/// least `width` columns.
+fn columns_chunk<'a>(selector: &ColumnSelector, width: usize, chunk: &'a [u8]) -> ColumnChunk<'a> {
+    let mut out = ColumnChunk { columns: vec![Vec::new(); width], rows: 0 };
+    let mut cells: Vec<(&'a [u8], bool)> = Vec::new();
+
+    let mut end_row = |cells: &mut Vec<(&'a [u8], bool)>| {
+        let mut k = 0;
+        selector.for_each(cells.len(), |col| {
+            if k == out.columns.len() {
+                // A column first seen here is empty in the rows before.
+                out.columns.push(vec![Cow::Borrowed(&b""[..]); out.rows]);
+            }
+            out.columns[k].push(col.map_or(Cow::Borrowed(&b""[..]), |col| cell(cells[col].0, cells[col].1)));
+            k += 1;
+        });
+        for column in &mut out.columns[k..] {
+            column.push(Cow::Borrowed(&b""[..]));
+        }
+        out.rows += 1;
+        cells.clear();
+    };
+
+    // Cells no row width can select are kept as placeholders, never unescaped.
+    let push = |cells: &mut Vec<(&'a [u8], bool)>, raw: &'a [u8], escaped: bool| {
+        let col = cells.len();
+        cells.push(if selector.may_select(col) { (raw, escaped) } else { (&b""[..], false) });
+    };
+
+    let (rest, escaped) = scan_lines(chunk, |start, end, escaped| {
+        if end > start {
+            push(&mut cells, &chunk[start..end], escaped);
+        } else {
+            end_row(&mut cells);
+        }
+    });
+    if rest < chunk.len() {
+        push(&mut cells, &chunk[rest..], escaped);
     }
+    if !cells.is_empty() {
+        end_row(&mut cells);
+    }
+    out
+}
 
-    /// Write a single complete row. Each cell is escaped and `\n`-terminated;
-    /// an extra `\n` terminates the row.
-    ///
-    /// Accepts any cell type that implements `AsRef<[u8]>`: `&[u8]`, `Vec<u8>`,
-    /// `&str`, `String`, etc.
-    pub fn write_row<C: AsRef<[u8]>>(&mut self, row: &[C]) -> io::Result<()> {
-        for cell in row {
-            self.inner.write_all(&escape_bytes(cell.as_ref()))?;
-            self.inner.write_all(b"\n")?;
+/// Concatenate per-chunk column buffers into at least `width` columns,
+/// padding chunks that have fewer columns than the widest.
+#[cfg(feature = "parallel")]
+fn concat_columns(chunks: Vec<ColumnChunk<'_>>, width: usize) -> Vec<Vec<Cow<'_, [u8]>>> {
+    let width = chunks.iter().map(|chunk| chunk.columns.len()).fold(width, usize::max);
+    let rows = chunks.iter().map(|chunk| chunk.rows).sum();
+    let mut columns: Vec<Vec<Cow<[u8]>>> = (0..width).map(|_| Vec::with_capacity(rows)).collect();
+    for chunk in chunks {
+        let mut chunk_columns = chunk.columns.into_iter();
+        for column in &mut columns {
+            match chunk_columns.next() {
+                Some(cells) => column.extend(cells),
+                None => column.resize(column.len() + chunk.rows, Cow::Borrowed(&b""[..])),
+            }
         }
-        self.inner.write_all(b"\n")
     }
+    columns
+}
 
-    /// Recover the inner writer.
-    pub fn into_inner(self) -> W {
-        self.inner
+// ── Lazy row iteration ───────────────────────────────────────────────
+
+/// Iterate over the rows of `input` without decoding them up front.
+///
+/// Each step finds the next row end with memmem; cells are split and
+/// unescaped only when a [`RowRef`] is read, so memory stays O(1) per row.
+///
+/// ```
+/// let input = b"name\nage\n\nAlice\n30\n\n";
+/// let ages: Vec<_> = nsv::rows(input).filter_map(|row| row.get(1)).collect();
+/// assert_eq!(ages, [&b"age"[..], b"30"]);
+/// ```
+pub fn rows(input: &[u8]) -> Rows<'_> {
+    Rows { input, pos: 0 }
+}
+
+/// Iterator over the rows of an input. See [`rows`].
+#[derive(Debug, Clone)]
+pub struct Rows<'a> {
+    input: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Iterator for Rows<'a> {
+    type Item = RowRef<'a>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let input = self.input;
+        let start = self.pos;
+        if start >= input.len() {
+            return None;
+        }
+        let end = boundaries::next_row_start(input, start + 1);
+        self.pos = end;
+        Some(row_at(input, start, end))
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+impl std::iter::FusedIterator for Rows<'_> {}
 
-    /// Convert Cow cells to owned for comparison with pre-built Vec<Vec<Vec<u8>>> data.
-    fn owned(rows: Vec<Vec<Cow<[u8]>>>) -> Vec<Vec<Vec<u8>>> {
-        rows.into_iter()
-            .map(|row| row.into_iter().map(|c| c.into_owned()).collect())
-            .collect()
+/// The row spanning `input[start..end]`, between two consecutive row starts
+/// (or the last one and the end of input).
+fn row_at(input: &[u8], start: usize, end: usize) -> RowRef<'_> {
+    // The row's last byte is its terminator when that LF is at a line start.
+    let terminated = input[end - 1] == b'\n' && (end - 1 == start || input[end - 2] == b'\n');
+    let raw = if terminated { &input[start..end - 1] } else { &input[start..end] };
+    RowRef { raw }
+}
+
+/// One row of an input, decoded on access.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct RowRef<'a> {
+    raw: &'a [u8],
+}
+
+impl<'a> RowRef<'a> {
+    /// The row's encoded cells, each with its LF, without the row terminator.
+    pub fn raw(&self) -> &'a [u8] {
+        self.raw
     }
 
-    #[test]
-    fn test_simple_table() {
-        let nsv = "col1\ncol2\n\na\nb\n\nc\nd\n";
-        let result = decode(nsv);
-        assert_eq!(
-            result,
-            vec![
-                vec!["col1".to_string(), "col2".to_string()],
-                vec!["a".to_string(), "b".to_string()],
-                vec!["c".to_string(), "d".to_string()],
-            ]
-        );
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        let unterminated = self.raw.last().is_some_and(|&b| b != b'\n');
+        memchr::memchr_iter(b'\n', self.raw).count() + usize::from(unterminated)
     }
 
-    #[test]
-    fn test_empty_fields() {
-        let nsv = "a\n\\\nb\n\n\\\nc\n\\\n";
-        let result = decode(nsv);
-        assert_eq!(
-            result,
-            vec![
-                vec!["a".to_string(), "".to_string(), "b".to_string()],
-                vec!["".to_string(), "c".to_string(), "".to_string()],
-            ]
-        );
+    pub fn is_empty(&self) -> bool {
+        self.raw.is_empty()
     }
 
-    #[test]
-    fn test_escape_sequences() {
-        let nsv = "Line 1\\nLine 2\nBackslash: \\\\\nNot a newline: \\\\n\n";
-        let result = decode(nsv);
-        assert_eq!(
-            result,
-            vec![vec![
-                "Line 1\nLine 2".to_string(),
-                "Backslash: \\".to_string(),
-                "Not a newline: \\n".to_string()
-            ],]
+    /// The cell at column `i`, unescaped.
+    pub fn get(&self, i: usize) -> Option<Cow<'a, [u8]>> {
+        self.iter().nth(i)
+    }
+
+    /// The cells, unescaped one at a time as the iterator advances.
+    pub fn iter(&self) -> Cells<'a> {
+        Cells { rest: self.raw }
+    }
+}
+
+impl<'a> IntoIterator for RowRef<'a> {
+    type Item = Cow<'a, [u8]>;
+    type IntoIter = Cells<'a>;
+
+    fn into_iter(self) -> Cells<'a> {
+        self.iter()
+    }
+}
+
+/// Iterator over the cells of a [`RowRef`].
+#[derive(Debug, Clone)]
+pub struct Cells<'a> {
+    rest: &'a [u8],
+}
+
+impl<'a> Iterator for Cells<'a> {
+    type Item = Cow<'a, [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.rest.is_empty() {
+            return None;
+        }
+        let raw = match memchr::memchr(b'\n', self.rest) {
+            Some(lf) => {
+                let raw = &self.rest[..lf];
+                self.rest = &self.rest[lf + 1..];
+                raw
+            }
+            None => std::mem::take(&mut self.rest),
+        };
+        Some(unescape_bytes(raw))
+    }
+
+    fn nth(&mut self, n: usize) -> Option<Self::Item> {
+        // Skip cells without unescaping them.
+        for _ in 0..n {
+            match memchr::memchr(b'\n', self.rest) {
+                Some(lf) => self.rest = &self.rest[lf + 1..],
+                None => self.rest = &[],
+            }
+        }
+        self.next()
+    }
+}
+
+impl std::iter::FusedIterator for Cells<'_> {}
+
+// ── Parallel row iteration ───────────────────────────────────────────
+
+/// Decode the rows of `input` in parallel, without collecting them.
+///
+/// The input is cut at row starts into a few chunks per thread, and each chunk
+/// yields its rows lazily, so a `filter`/`map`/`reduce` pipeline never holds
+/// the whole table. Collecting keeps the input order.
+///
+/// ```
+/// use rayon::prelude::*;
+///
+/// let input = b"a\n1\n\nb\n2\n\nc\n3\n\n";
+/// let total: u32 = nsv::par_rows(input)
+///     .map(|row| std::str::from_utf8(&row[1]).unwrap().parse::<u32>().unwrap())
+///     .sum();
+/// assert_eq!(total, 6);
+/// ```
+#[cfg(feature = "parallel")]
+pub fn par_rows(input: &[u8]) -> impl ParallelIterator<Item = Vec<Cow<'_, [u8]>>> {
+    par_rows_chunked(input, row_chunk_count(input.len()))
+}
+
+/// [`par_rows`] as an [`IndexedParallelIterator`], for `enumerate`, `zip`,
+/// `skip`/`take` and `collect_into_vec`.
+///
+/// Finding each row's position first costs one scan over the input and one
+/// offset per row; the rows themselves are still decoded on demand.
+#[cfg(feature = "parallel")]
+pub fn par_rows_indexed(input: &[u8]) -> impl IndexedParallelIterator<Item = Vec<Cow<'_, [u8]>>> {
+    let starts = row_starts(input, row_chunk_count(input.len()));
+    (0..starts.len() - 1)
+        .into_par_iter()
+        .map(move |i| row_at(input, starts[i], starts[i + 1]).iter().collect())
+}
+
+/// Chunks for a parallel pass over `len` bytes: a few per thread, none tiny.
+#[cfg(feature = "parallel")]
+fn row_chunk_count(len: usize) -> usize {
+    let max_chunks = rayon::current_num_threads() * CHUNKS_PER_THREAD;
+    (len / MIN_CHUNK_SIZE).clamp(1, max_chunks)
+}
+
+#[cfg(feature = "parallel")]
+fn par_rows_chunked(input: &[u8], num_chunks: usize) -> impl ParallelIterator<Item = Vec<Cow<'_, [u8]>>> {
+    let chunks: Vec<&[u8]> = boundaries::split_points(input, num_chunks).windows(2).map(|w| &input[w[0]..w[1]]).collect();
+    chunks.into_par_iter().flat_map_iter(|chunk| rows(chunk).map(|row| row.iter().collect()))
+}
+
+/// The start of every row of `input`, found in parallel over `num_chunks`
+/// chunks, followed by `input.len()`.
+#[cfg(feature = "parallel")]
+fn row_starts(input: &[u8], num_chunks: usize) -> Vec<usize> {
+    let splits = boundaries::split_points(input, num_chunks);
+    let per_chunk: Vec<Vec<usize>> = splits
+        .par_windows(2)
+        .map(|w| {
+            let (from, to) = (w[0], w[1]);
+            let mut starts = vec![from];
+            let mut line_start = from;
+            for lf in memchr::memchr_iter(b'\n', &input[from..to]).map(|at| from + at) {
+                // An LF at a line start ends a row.
+                if lf == line_start && lf + 1 < to {
+                    starts.push(lf + 1);
+                }
+                line_start = lf + 1;
+            }
+            starts
+        })
+        .collect();
+
+    let mut starts: Vec<usize> = per_chunk.into_iter().flatten().collect();
+    starts.push(input.len());
+    starts
+}
+
+// ── Counting ─────────────────────────────────────────────────────────
+
+/// Number of rows in `input`, as [`decode_bytes`] would return them, without
+/// decoding anything.
+///
+/// Counts row ends (LF pairs) with a vectorized byte scan, in parallel above
+/// [`PARALLEL_THRESHOLD`].
+pub fn count_rows(input: &[u8]) -> usize {
+    #[cfg(feature = "parallel")]
+    if input.len() >= PARALLEL_THRESHOLD {
+        let points = boundaries::split_points(input, row_chunk_count(input.len()));
+        return points.par_windows(2).map(|w| count_rows_sequential(&input[w[0]..w[1]])).sum();
+    }
+
+    count_rows_sequential(input)
+}
+
+fn count_rows_sequential(input: &[u8]) -> usize {
+    let Some(&last) = input.last() else {
+        return 0;
+    };
+    // A row end is an LF at a line start: at 0, or right after another LF.
+    let ends = usize::from(input[0] == b'\n') + count_lf_pairs(input);
+    // Anything after the last row end is one more, unterminated, row.
+    let terminated = last == b'\n' && (input.len() == 1 || input[input.len() - 2] == b'\n');
+    ends + usize::from(!terminated)
+}
+
+/// Row and cell counts of an input. See [`shape`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct Shape {
+    pub rows: usize,
+    /// Fewest cells in a row (0 when there are no rows).
+    pub min_cols: usize,
+    /// Most cells in a row.
+    pub max_cols: usize,
+    /// Cells in all rows.
+    pub cells: usize,
+}
+
+impl Shape {
+    /// The shape of two inputs, one after the other, each ending at a row end.
+    fn merge(self, other: Shape) -> Shape {
+        if self.rows == 0 {
+            return other;
+        }
+        if other.rows == 0 {
+            return self;
+        }
+        Shape {
+            rows: self.rows + other.rows,
+            min_cols: self.min_cols.min(other.min_cols),
+            max_cols: self.max_cols.max(other.max_cols),
+            cells: self.cells + other.cells,
+        }
+    }
+}
+
+/// Row and cell counts of `input`, matching what [`decode_bytes`] would return,
+/// from a scan for LFs only: no cell is unescaped or allocated.
+///
+/// Runs in parallel above [`PARALLEL_THRESHOLD`].
+///
+/// ```
+/// let shape = nsv::shape(b"a\nb\n\nc\n\n\n");
+/// assert_eq!((shape.rows, shape.min_cols, shape.max_cols, shape.cells), (3, 0, 2, 3));
+/// ```
+pub fn shape(input: &[u8]) -> Shape {
+    #[cfg(feature = "parallel")]
+    if input.len() >= PARALLEL_THRESHOLD {
+        let points = boundaries::split_points(input, row_chunk_count(input.len()));
+        return points
+            .par_windows(2)
+            .map(|w| shape_sequential(&input[w[0]..w[1]]))
+            .reduce(Shape::default, Shape::merge);
+    }
+
+    shape_sequential(input)
+}
+
+fn shape_sequential(input: &[u8]) -> Shape {
+    let mut counter = ShapeCounter::default();
+    counter.feed(input);
+    counter.finish()
+}
+
+/// [`shape`] of everything `reader` yields, read through in buffer-sized pieces.
+pub fn shape_reader<R: Read>(reader: R) -> io::Result<Shape> {
+    let mut reader = io::BufReader::new(reader);
+    let mut counter = ShapeCounter::default();
+    loop {
+        let buf = match reader.fill_buf() {
+            Ok(buf) => buf,
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+            Err(e) => return Err(e),
+        };
+        if buf.is_empty() {
+            return Ok(counter.finish());
+        }
+        counter.feed(buf);
+        let len = buf.len();
+        reader.consume(len);
+    }
+}
+
+/// Incremental [`Shape`] over input fed in pieces.
+#[derive(Default)]
+struct ShapeCounter {
+    shape: Shape,
+    /// Cells of the row in progress.
+    cols: usize,
+    /// The line in progress has at least one byte.
+    line_open: bool,
+}
+
+impl ShapeCounter {
+    fn feed(&mut self, input: &[u8]) {
+        let mut line_start = 0;
+        for lf in memchr::memchr_iter(b'\n', input) {
+            if lf > line_start || self.line_open {
+                self.cols += 1;
+            } else {
+                self.end_row();
+            }
+            self.line_open = false;
+            line_start = lf + 1;
+        }
+        if line_start < input.len() {
+            self.line_open = true;
+        }
+    }
+
+    fn end_row(&mut self) {
+        let cols = std::mem::take(&mut self.cols);
+        self.shape = self.shape.merge(Shape { rows: 1, min_cols: cols, max_cols: cols, cells: cols });
+    }
+
+    fn finish(mut self) -> Shape {
+        if self.line_open {
+            self.cols += 1;
+        }
+        // A non-empty remainder is an unterminated row, which decodes too.
+        if self.cols > 0 {
+            self.end_row();
+        }
+        self.shape
+    }
+}
+
+/// Encode a seqseq into an NSV string.
+pub fn encode(data: &[Vec<String>]) -> String {
+    let result = encode_rows(data);
+
+    // Safety: encoding only inserts ASCII bytes (\, n, LF) — preserves UTF-8.
+    String::from_utf8(result).unwrap()
+}
+
+/// Encode a seqseq of byte vectors into raw NSV bytes.
+pub fn encode_bytes(data: &[Vec<Vec<u8>>]) -> Vec<u8> {
+    encode_rows(data)
+}
+
+/// Pick the sequential or parallel encoder based on the (unescaped) payload size.
+fn encode_rows<C: AsRef<[u8]> + Sync>(data: &[Vec<C>]) -> Vec<u8> {
+    #[cfg(feature = "parallel")]
+    if data.len() > 1 && encoded_len_hint(data) >= PARALLEL_THRESHOLD {
+        return encode_rows_parallel(data);
+    }
+
+    encode_rows_sequential(data)
+}
+
+/// Lower bound on the encoded size: cell bytes plus terminators, before escaping.
+#[cfg(feature = "parallel")]
+fn encoded_len_hint<C: AsRef<[u8]>>(data: &[Vec<C>]) -> usize {
+    data.iter()
+        .map(|row| row.iter().map(|cell| cell.as_ref().len() + 1).sum::<usize>() + 1)
+        .sum()
+}
+
+/// Sequential implementation.
+fn encode_rows_sequential<C: AsRef<[u8]>>(data: &[Vec<C>]) -> Vec<u8> {
+    let mut result = Vec::new();
+
+    for row in data {
+        for cell in row {
+            escape_into(cell.as_ref(), &mut result);
+            result.push(b'\n');
+        }
+        result.push(b'\n');
+    }
+
+    result
+}
+
+/// Chunked parallel implementation for large inputs.
+///
+/// Splits the rows into N contiguous ranges (one per core), encodes each range
+/// into its own buffer, then joins the buffers in order. Rows are independent
+/// in NSV, so the output is byte-identical to the sequential encoder.
+#[cfg(feature = "parallel")]
+fn encode_rows_parallel<C: AsRef<[u8]> + Sync>(data: &[Vec<C>]) -> Vec<u8> {
+    let num_threads = rayon::current_num_threads();
+    let rows_per_chunk = data.len().div_ceil(num_threads);
+
+    let chunk_results: Vec<Vec<u8>> = data
+        .par_chunks(rows_per_chunk)
+        .map(encode_rows_sequential)
+        .collect();
+
+    let total_len: usize = chunk_results.iter().map(|c| c.len()).sum();
+    let mut result = Vec::with_capacity(total_len);
+    for chunk in chunk_results {
+        result.extend_from_slice(&chunk);
+    }
+    result
+}
+
+/// A single warning found during validation.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Warning {
+    pub kind: WarningKind,
+    pub pos: usize,
+    pub line: usize,
+    pub col: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum WarningKind {
+    /// `\` followed by a byte other than `n` or `\`
+    UnknownEscape(u8),
+    /// `\` immediately before LF or at EOF
+    DanglingBackslash,
+    /// Non-empty input not ending with LF
+    NoTerminalLf,
+}
+
+/// Report edge cases in raw NSV input without altering parsing behavior.
+///
+/// Warns on unknown escape sequences, dangling backslashes, and missing terminal LF.
+/// Positions are byte offsets; line and col are 1-indexed.
+pub fn check(input: &[u8]) -> Vec<Warning> {
+    let mut warnings = Vec::new();
+    let mut line: usize = 1;
+    let mut line_start: usize = 0;
+    let mut warn = |kind, pos, line, line_start| warnings.push(Warning { kind, pos, line, col: pos - line_start + 1 });
+
+    for event in lexer::lex(input) {
+        match event {
+            Event::Escape { kind: EscapeKind::Unknown(b), range } => warn(WarningKind::UnknownEscape(b), range.start, line, line_start),
+            Event::Escape { kind: EscapeKind::Dangling, range } => warn(WarningKind::DanglingBackslash, range.start, line, line_start),
+            // A cell cut off by the end of the input ends without an LF
+            Event::CellEnd(at) | Event::RowEnd(at) if at < input.len() => {
+                line += 1;
+                line_start = at + 1;
+            }
+            Event::Eof { terminated: false } => warn(WarningKind::NoTerminalLf, input.len(), line, line_start),
+            _ => {}
+        }
+    }
+
+    warnings
+}
+
+// ── Streaming ────────────────────────────────────────────────────────
+
+/// Streaming NSV reader. Yields one complete row of byte vectors at a time.
+///
+/// On EOF, returns `Ok(None)` without discarding buffered state — calling
+/// `next_row()` again after more data arrives resumes where it left off.
+pub struct Reader<R> {
+    inner: io::BufReader<R>,
+    line_buf: Vec<u8>,
+    row: Vec<Vec<u8>>,
+    /// Inside a row being skipped: its end hasn't been read yet.
+    skipping: bool,
+    /// While skipping, whether the last byte consumed ended a line.
+    skip_line_start: bool,
+    selector: Option<ColumnSelector>,
+}
+
+impl<R: io::Read> Reader<R> {
+    pub fn new(reader: R) -> Self {
+        Self::from_buf_reader(io::BufReader::new(reader))
+    }
+
+    pub fn from_buf_reader(reader: io::BufReader<R>) -> Self {
+        Reader { inner: reader, line_buf: Vec::new(), row: Vec::new(), skipping: false, skip_line_start: true, selector: None }
+    }
+
+    /// Yield only the columns picked by `selector`. See [`ColumnSelector`].
+    ///
+    /// Cells that no row width can select are dropped as they are read. The
+    /// rest are kept escaped until the row ends and its width is known, so
+    /// [`partial_row`](Self::partial_row) holds escaped bytes while a selector
+    /// is set, and empty placeholders for the dropped cells.
+    pub fn select(mut self, selector: ColumnSelector) -> Self {
+        self.selector = Some(selector);
+        self
+    }
+
+    pub fn next_row(&mut self) -> io::Result<Option<Vec<Vec<u8>>>> {
+        if self.skipping && !self.skip_row_rest()? {
+            return Ok(None);
+        }
+        let mut byte = [0u8; 1];
+        loop {
+            match self.inner.read(&mut byte) {
+                Ok(0) => return Ok(None),
+                Err(e) => return Err(e),
+                Ok(_) if byte[0] != b'\n' => self.line_buf.push(byte[0]),
+                Ok(_) if self.line_buf.is_empty() => return Ok(Some(self.finish_row())),
+                Ok(_) => {
+                    let cell = match &self.selector {
+                        None => unescape_bytes(&self.line_buf).into_owned(),
+                        Some(selector) if selector.may_select(self.row.len()) => self.line_buf.clone(),
+                        Some(_) => Vec::new(),
+                    };
+                    self.row.push(cell);
+                    self.line_buf.clear();
+                }
+            }
+        }
+    }
+
+    /// Take the completed row, resolving the selector against its width.
+    fn finish_row(&mut self) -> Vec<Vec<u8>> {
+        let row = std::mem::take(&mut self.row);
+        let Some(selector) = &self.selector else {
+            return row;
+        };
+        let mut selected = Vec::new();
+        selector.for_each(row.len(), |col| {
+            selected.push(col.map_or_else(Vec::new, |col| unescape_bytes(&row[col]).into_owned()));
+        });
+        selected
+    }
+
+    /// Skip the next `n` rows, including the one partially read, without
+    /// unescaping or buffering them.
+    ///
+    /// Returns how many rows were skipped: fewer than `n` when the input runs
+    /// out first. A row cut off that way stays pending; the next
+    /// `skip_rows` or [`next_row`](Self::next_row) finishes skipping it once
+    /// more input is available.
+    pub fn skip_rows(&mut self, n: usize) -> io::Result<usize> {
+        for skipped in 0..n {
+            if !self.skipping {
+                self.skipping = true;
+                self.skip_line_start = self.line_buf.is_empty();
+                self.line_buf.clear();
+                self.row.clear();
+            }
+            if !self.skip_row_rest()? {
+                return Ok(skipped);
+            }
+        }
+        Ok(n)
+    }
+
+    /// Consume input up to the end of the row being skipped. Returns `false`
+    /// if the input ran out first.
+    fn skip_row_rest(&mut self) -> io::Result<bool> {
+        loop {
+            let buf = self.inner.fill_buf()?;
+            if buf.is_empty() {
+                return Ok(false);
+            }
+            // The row ends at the first LF that starts a line.
+            let line_start = self.skip_line_start;
+            let end = memchr::memchr_iter(b'\n', buf).find(|&lf| if lf == 0 { line_start } else { buf[lf - 1] == b'\n' });
+            if let Some(lf) = end {
+                self.inner.consume(lf + 1);
+                self.skipping = false;
+                return Ok(true);
+            }
+            let len = buf.len();
+            self.skip_line_start = buf[len - 1] == b'\n';
+            self.inner.consume(len);
+        }
+    }
+
+    /// Completed cells of the row currently being assembled.
+    pub fn partial_row(&self) -> &[Vec<u8>] {
+        &self.row
+    }
+
+    /// Bytes accumulated for the cell currently being read (not yet unescaped).
+    pub fn partial_cell(&self) -> &[u8] {
+        &self.line_buf
+    }
+
+    /// Recover the inner `BufReader`.
+    pub fn into_inner(self) -> io::BufReader<R> {
+        self.inner
+    }
+}
+
+impl<R: io::Read> Iterator for Reader<R> {
+    type Item = io::Result<Vec<Vec<u8>>>;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.next_row().transpose()
+    }
+}
+
+/// Streaming NSV writer. Wraps any `W: Write` and writes one row at a time.
+///
+/// No internal buffering — wrap the inner writer in `BufWriter` if needed.
+pub struct Writer<W> {
+    inner: W,
+}
+
+impl<W: Write> Writer<W> {
+    pub fn new(writer: W) -> Self {
+        Writer { inner: writer }
+    }
+
+    /// Write a single complete row. Each cell is escaped and `\n`-terminated;
+    /// an extra `\n` terminates the row.
+    ///
+    /// Accepts any cell type that implements `AsRef<[u8]>`: `&[u8]`, `Vec<u8>`,
+    /// `&str`, `String`, etc.
+    pub fn write_row<C: AsRef<[u8]>>(&mut self, row: &[C]) -> io::Result<()> {
+        for cell in row {
+            self.inner.write_all(&escape_bytes(cell.as_ref()))?;
+            self.inner.write_all(b"\n")?;
+        }
+        self.inner.write_all(b"\n")
+    }
+
+    /// Recover the inner writer.
+    pub fn into_inner(self) -> W {
+        self.inner
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::random_input;
+
+    /// Convert Cow cells to owned for comparison with pre-built Vec<Vec<Vec<u8>>> data.
+    fn owned(rows: Vec<Vec<Cow<[u8]>>>) -> Vec<Vec<Vec<u8>>> {
+        rows.into_iter()
+            .map(|row| row.into_iter().map(|c| c.into_owned()).collect())
+            .collect()
+    }
+
+    #[test]
+    fn test_simple_table() {
+        let nsv = "col1\ncol2\n\na\nb\n\nc\nd\n";
+        let result = decode(nsv);
+        assert_eq!(
+            result,
+            vec![
+                vec!["col1".to_string(), "col2".to_string()],
+                vec!["a".to_string(), "b".to_string()],
+                vec!["c".to_string(), "d".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_empty_fields() {
+        let nsv = "a\n\\\nb\n\n\\\nc\n\\\n";
+        let result = decode(nsv);
+        assert_eq!(
+            result,
+            vec![
+                vec!["a".to_string(), "".to_string(), "b".to_string()],
+                vec!["".to_string(), "c".to_string(), "".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_escape_sequences() {
+        let nsv = "Line 1\\nLine 2\nBackslash: \\\\\nNot a newline: \\\\n\n";
+        let result = decode(nsv);
+        assert_eq!(
+            result,
+            vec![vec![
+                "Line 1\nLine 2".to_string(),
+                "Backslash: \\".to_string(),
+                "Not a newline: \\n".to_string()
+            ],]
+        );
+    }
+
+    #[test]
+    fn test_empty_rows() {
+        let nsv = "first\n\n\n\nsecond\n";
+        let result = decode(nsv);
+        assert_eq!(
+            result,
+            vec![
+                vec!["first".to_string()],
+                vec![],
+                vec![],
+                vec!["second".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_multiple_empty_rows() {
+        let nsv = "a\n\n\n\n\nb\n";
+        let result = decode(nsv);
+        assert_eq!(
+            result,
+            vec![
+                vec!["a".to_string()],
+                vec![],
+                vec![],
+                vec![],
+                vec!["b".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_roundtrip() {
+        let original = vec![
+            vec!["col1".to_string(), "col2".to_string()],
+            vec!["a".to_string(), "b".to_string()],
+            vec!["".to_string(), "value\\with\\backslash".to_string()],
+            vec!["multi\nline".to_string(), "normal".to_string()],
+        ];
+
+        let encoded = encode(&original);
+        let decoded = decode(&encoded);
+        assert_eq!(original, decoded);
+    }
+
+    #[test]
+    fn test_unrecognized_escape() {
+        let nsv = "\\x41\\t\\r\n";
+        let result = decode(nsv);
+        assert_eq!(result, vec![vec!["\\x41\\t\\r".to_string()],]);
+    }
+
+    #[test]
+    fn test_dangling_backslash() {
+        let nsv = "text\\\n";
+        let result = decode(nsv);
+        assert_eq!(result, vec![vec!["text".to_string()],]);
+    }
+
+    #[test]
+    fn test_empty_input() {
+        let result = decode("");
+        assert_eq!(result, Vec::<Vec<String>>::new());
+    }
+
+    #[test]
+    fn test_no_trailing_newline() {
+        let nsv = "a\nb";
+        let result = decode(nsv);
+        assert_eq!(result, vec![vec!["a".to_string(), "b".to_string()],]);
+    }
+
+    #[test]
+    fn test_only_empty_rows() {
+        let nsv = "\n\n\n\n";
+        let result = decode(nsv);
+        assert_eq!(
+            result,
+            vec![
+                Vec::<String>::new(),
+                Vec::<String>::new(),
+                Vec::<String>::new(),
+                Vec::<String>::new(),
+            ]
         );
     }
 
     #[test]
-    fn test_empty_rows() {
-        let nsv = "first\n\n\n\nsecond\n";
-        let result = decode(nsv);
+    fn test_starts_with_empty_row() {
+        let nsv = "\n\nfirst\n";
+        let result = decode(nsv);
+        assert_eq!(
+            result,
+            vec![
+                Vec::<String>::new(),
+                Vec::<String>::new(),
+                vec!["first".to_string()],
+            ]
+        );
+    }
+
+    #[test]
+    fn test_large_file() {
+        // Generate ~10MB of data to verify parallel path is exercised
+        // (needs to exceed PARALLEL_THRESHOLD of 256 KiB)
+        let large_data: Vec<Vec<String>> = (0..100_000)
+            .map(|i| vec![format!("row{}", i), format!("data{}", i)])
+            .collect();
+
+        let encoded = encode(&large_data);
+
+        // Verify it's large enough to trigger parallel parsing
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+
+        let decoded = decode(&encoded);
+        assert_eq!(large_data, decoded);
+    }
+
+    #[test]
+    fn test_parallel_with_empty_rows() {
+        // Test parallel path with empty rows mixed in
+        let mut data = Vec::new();
+
+        for i in 0..40_000 {
+            data.push(vec![format!("value{}", i)]);
+
+            if i % 100 == 0 {
+                data.push(vec![]);
+            }
+        }
+
+        let encoded = encode(&data);
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+
+        let decoded = decode(&encoded);
+        assert_eq!(data, decoded);
+    }
+
+    #[test]
+    fn test_parallel_with_escape_sequences() {
+        // Test parallel path with cells containing escape sequences
+        let mut data = Vec::new();
+
+        for i in 0..20_000 {
+            data.push(vec![
+                format!("Line 1\nLine 2 {}", i),
+                format!("Backslash: \\ {}", i),
+                "".to_string(),
+            ]);
+        }
+
+        let encoded = encode(&data);
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+
+        let decoded = decode(&encoded);
+        assert_eq!(data, decoded);
+    }
+
+    // ── Byte-level tests ──
+
+    #[test]
+    fn test_bytes_roundtrip() {
+        let original: Vec<Vec<Vec<u8>>> = vec![
+            vec![b"col1".to_vec(), b"col2".to_vec()],
+            vec![b"a".to_vec(), b"b".to_vec()],
+            vec![b"".to_vec(), b"value\\with\\backslash".as_slice().to_vec()],
+            vec![b"multi\nline".to_vec(), b"normal".to_vec()],
+        ];
+
+        let encoded = encode_bytes(&original);
+        let decoded = owned(decode_bytes(&encoded));
+        assert_eq!(original, decoded);
+    }
+
+    #[test]
+    fn test_bytes_non_utf8() {
+        // Latin-1 bytes with values > 0x7F — not valid UTF-8
+        let cell1: Vec<u8> = vec![0xC0, 0xE9, 0xF1]; // àéñ in Latin-1
+        let cell2: Vec<u8> = vec![0xFF, 0xFE, 0x80]; // arbitrary high bytes
+        let original: Vec<Vec<Vec<u8>>> = vec![vec![cell1.clone(), cell2.clone()]];
+
+        let encoded = encode_bytes(&original);
+        let decoded = owned(decode_bytes(&encoded));
+        assert_eq!(original, decoded);
+    }
+
+    #[test]
+    fn test_bytes_empty_cells_and_rows() {
+        let original: Vec<Vec<Vec<u8>>> = vec![
+            vec![b"a".to_vec(), b"".to_vec(), b"b".to_vec()],
+            vec![],
+            vec![b"".to_vec()],
+        ];
+
+        let encoded = encode_bytes(&original);
+        let decoded = owned(decode_bytes(&encoded));
+        assert_eq!(original, decoded);
+    }
+
+    #[test]
+    fn test_bytes_with_special_ascii_bytes() {
+        // Verify that only 0x0A (LF), 0x5C (\), 0x6E (n) are structurally significant
+        let cell: Vec<u8> = (0u8..=255)
+            .filter(|&b| b != b'\n' && b != b'\\')
+            .collect();
+        let original = vec![vec![cell.clone()]];
+
+        let encoded = encode_bytes(&original);
+        let decoded = owned(decode_bytes(&encoded));
+        assert_eq!(original, decoded);
+    }
+
+    #[test]
+    fn test_decode_bytes_matches_decode_str() {
+        // Confirm decode(s) and decode_bytes(s.as_bytes()) produce equivalent structures
+        let inputs = vec![
+            "col1\ncol2\n\na\nb\n\nc\nd\n",
+            "a\n\\\nb\n\n\\\nc\n\\\n",
+            "Line 1\\nLine 2\nBackslash: \\\\\n",
+            "first\n\n\n\nsecond\n",
+            "",
+            "\n\n\n\n",
+        ];
+
+        for input in inputs {
+            let str_result = decode(input);
+            let byte_result = decode_bytes(input.as_bytes());
+
+            // Convert byte result to strings for comparison
+            let byte_as_str: Vec<Vec<String>> = byte_result
+                .into_iter()
+                .map(|row| {
+                    row.into_iter()
+                        .map(|cell| String::from_utf8(cell.into_owned()).unwrap())
+                        .collect()
+                })
+                .collect();
+
+            assert_eq!(str_result, byte_as_str, "mismatch for input: {:?}", input);
+        }
+    }
+
+    #[test]
+    fn test_bytes_large_parallel() {
+        // Generate enough data to trigger parallel path
+        let large_data: Vec<Vec<Vec<u8>>> = (0..100_000)
+            .map(|i| {
+                vec![
+                    format!("row{}", i).into_bytes(),
+                    format!("data{}", i).into_bytes(),
+                ]
+            })
+            .collect();
+
+        let encoded = encode_bytes(&large_data);
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+
+        let decoded = owned(decode_bytes(&encoded));
+        assert_eq!(large_data, decoded);
+    }
+
+    // ── Cell escaping ──
+
+    /// Byte-at-a-time reference for `unescape_bytes`.
+    fn unescape_reference(s: &[u8]) -> Vec<u8> {
+        if s == b"\\" {
+            return Vec::new();
+        }
+        let mut out = Vec::new();
+        let mut escaped = false;
+        for &b in s {
+            if escaped {
+                match b {
+                    b'n' => out.push(b'\n'),
+                    b'\\' => out.push(b'\\'),
+                    _ => out.extend_from_slice(&[b'\\', b]),
+                }
+                escaped = false;
+            } else if b == b'\\' {
+                escaped = true;
+            } else {
+                out.push(b);
+            }
+        }
+        out
+    }
+
+    #[test]
+    fn test_escape_unescape_all_short_cells() {
+        // Every cell of length <= 4 over an alphabet that covers all the special cases
+        let alphabet = [b'\\', b'\n', b'n', b'x'];
+        let mut cells: Vec<Vec<u8>> = vec![vec![]];
+        for len in 1..=4 {
+            for mut i in 0..alphabet.len().pow(len) {
+                let mut cell = Vec::new();
+                for _ in 0..len {
+                    cell.push(alphabet[i % alphabet.len()]);
+                    i /= alphabet.len();
+                }
+                cells.push(cell);
+            }
+        }
+
+        for cell in &cells {
+            let escaped = escape_bytes(cell);
+            assert_eq!(unescape_bytes(&escaped), cell.as_slice(), "cell: {:?}", cell);
+            assert_eq!(unescape_bytes(cell), unescape_reference(cell), "cell: {:?}", cell);
+
+            let mut out = b"prefix".to_vec();
+            escape_into(cell, &mut out);
+            assert_eq!(&out[6..], &escaped[..]);
+
+            let mut out = b"prefix".to_vec();
+            unescape_into(cell, &mut out);
+            assert_eq!(&out[6..], unescape_reference(cell).as_slice());
+        }
+    }
+
+    #[test]
+    fn test_escape_unescape_long_runs() {
+        let cell = [&b"a"[..]; 100].join(&b"\n\\"[..]);
+        let escaped = escape_bytes(&cell);
+        assert_eq!(escaped.len(), cell.len() + 2 * 99);
+        assert_eq!(unescape_bytes(&escaped), &cell[..]);
+
+        assert!(matches!(escape_bytes(b"plain"), Cow::Borrowed(_)));
+        assert!(matches!(unescape_bytes(b"plain"), Cow::Borrowed(_)));
+    }
+
+    #[test]
+    fn test_escape_into_unescape_into_append() {
+        let mut buf = Vec::new();
+        for cell in [&b"a\nb"[..], b"", b"c\\"] {
+            escape_into(cell, &mut buf);
+            buf.push(b'\n');
+        }
+        assert_eq!(buf, b"a\\nb\n\\\nc\\\\\n");
+
+        let mut out = Vec::new();
+        for cell in buf.split(|&b| b == b'\n').filter(|c| !c.is_empty()) {
+            unescape_into(cell, &mut out);
+            out.push(b'|');
+        }
+        assert_eq!(out, b"a\nb||c\\|");
+    }
+
+    // ── Structural index ──
+
+    /// Byte-at-a-time reference decoder (the original scalar loop).
+    fn decode_reference(input: &[u8]) -> Vec<Vec<Vec<u8>>> {
+        let mut data = Vec::new();
+        let mut row = Vec::new();
+        let mut start = 0;
+        for (pos, &b) in input.iter().enumerate() {
+            if b == b'\n' {
+                if pos > start {
+                    row.push(unescape_reference(&input[start..pos]));
+                } else {
+                    data.push(std::mem::take(&mut row));
+                }
+                start = pos + 1;
+            }
+        }
+        if start < input.len() {
+            row.push(unescape_reference(&input[start..]));
+        }
+        if !row.is_empty() {
+            data.push(row);
+        }
+        data
+    }
+
+    #[test]
+    fn test_scan_lines_matches_reference() {
+        for seed in 0..200 {
+            let input = random_input(seed, (seed as usize * 37) % 500);
+            assert_eq!(owned(decode_rows(&AllColumns, &input)), decode_reference(&input), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_scan_lines_across_batches() {
+        // Several stage-1 batches, with escaped and unescaped lines on both sides of each batch edge
+        let mut input = Vec::new();
+        for i in 0..INDEX_BATCH * 3 + 7 {
+            match i % 5 {
+                0 => input.extend_from_slice(b"\n"),
+                1 => input.extend_from_slice(b"esc\\\\aped\\n\n"),
+                2 => input.extend_from_slice(b"\\\n"),
+                _ => input.extend_from_slice(format!("plain{}\n", i).as_bytes()),
+            }
+        }
+        input.extend_from_slice(b"tail\\n");
+        assert_eq!(owned(decode_rows(&AllColumns, &input)), decode_reference(&input));
+
+        let projected = owned(decode_rows(&Projection::new(&[1, 0]), &input));
+        let full = decode_reference(&input);
+        assert_eq!(projected.len(), full.len());
+        for (p, f) in projected.iter().zip(&full) {
+            assert_eq!(p[0], f.get(1).cloned().unwrap_or_default());
+            assert_eq!(p[1], f.first().cloned().unwrap_or_default());
+        }
+    }
+
+    // ── Intra-row splitting ──
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_single_giant_row() {
+        let row: Vec<Vec<u8>> = (0..200_000)
+            .map(|i| match i % 50 {
+                0 => Vec::new(),
+                1 => format!("multi\nline{}", i).into_bytes(),
+                _ => format!("cell{}", i).into_bytes(),
+            })
+            .collect();
+        let encoded = encode_bytes(std::slice::from_ref(&row));
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+
+        assert_eq!(owned(decode_bytes(&encoded)), vec![row.clone()]);
+        assert_eq!(owned(decode_rows_chunked(&AllColumns, &encoded, 64, &Tracker::default()).unwrap()), vec![row.clone()]);
+
+        // Unterminated row (no trailing \n\n)
+        let unterminated = &encoded[..encoded.len() - 1];
+        assert_eq!(owned(decode_rows_chunked(&AllColumns, unterminated, 64, &Tracker::default()).unwrap()), vec![row]);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_chunks_match_sequential() {
+        for seed in 0..100 {
+            let input = random_input(seed, 300);
+            let expected = decode_reference(&input);
+            for num_chunks in [2, 3, 7, 16, 64, 300] {
+                assert_eq!(
+                    owned(decode_rows_chunked(&AllColumns, &input, num_chunks, &Tracker::default()).unwrap()),
+                    expected,
+                    "seed {}, {} chunks",
+                    seed,
+                    num_chunks
+                );
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_wide_rows() {
+        let data: Vec<Vec<String>> = (0..20)
+            .map(|r| (0..5_000).map(|c| format!("r{}c{}", r, c)).collect())
+            .collect();
+        let encoded = encode(&data);
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+        assert_eq!(decode(&encoded), data);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_projected_chunks_match_sequential() {
+        for seed in 0..100 {
+            let input = random_input(seed, 300);
+            for columns in [&[0][..], &[2, 0], &[1, 3, 5], &[7]] {
+                let expected = owned(decode_rows(&Projection::new(columns), &input));
+                for num_chunks in [2, 5, 16, 300] {
+                    let projection = Projection::new(columns);
+                    assert_eq!(
+                        owned(decode_rows_chunked(&projection, &input, num_chunks, &Tracker::default()).unwrap()),
+                        expected,
+                        "seed {}, columns {:?}, {} chunks",
+                        seed,
+                        columns,
+                        num_chunks
+                    );
+                }
+            }
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_parallel_skewed_input() {
+        // One dense, heavily escaped region followed by many small rows
+        let mut data: Vec<Vec<String>> = (0..50)
+            .map(|i| (0..2_000).map(|j| format!("\\{}\n{}", i, j)).collect())
+            .collect();
+        data.extend((0..50_000).map(|i| vec![format!("r{}", i), "".to_string()]));
+        let encoded = encode(&data);
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+
+        assert_eq!(decode(&encoded), data);
+        let projected = owned(decode_bytes_projected(encoded.as_bytes(), &[1999, 1]));
+        assert_eq!(projected.len(), data.len());
+        assert_eq!(projected[0][0], data[0][1999].as_bytes());
+        assert_eq!(projected[49][1], data[49][1].as_bytes());
+        assert_eq!(projected[50], vec![b"".to_vec(), b"".to_vec()]);
+    }
+
+    #[test]
+    fn test_decode_options_match_default() {
+        let data: Vec<Vec<String>> = (0..2_000)
+            .map(|i| vec![format!("r{}", i), format!("a\\b\n{}", i), String::new()])
+            .collect();
+        let encoded = encode(&data);
+        let input = encoded.as_bytes();
+        let expected = owned(decode_bytes(input));
+
+        let sequential = DecodeOptions::new().sequential(true);
+        assert_eq!(owned(decode_bytes_with(input, &sequential).unwrap()), expected);
+        assert_eq!(decode_with(&encoded, &sequential).unwrap(), data);
+
+        // A tiny threshold forces the parallel path even for small input
+        let eager = DecodeOptions::new().parallel_threshold(1);
+        assert_eq!(owned(decode_bytes_with(input, &eager).unwrap()), expected);
         assert_eq!(
-            result,
-            vec![
-                vec!["first".to_string()],
-                vec![],
-                vec![],
-                vec!["second".to_string()],
-            ]
+            owned(decode_bytes_projected_with(input, &[1, 0], &eager).unwrap()),
+            owned(decode_bytes_projected(input, &[1, 0])),
         );
+        assert!(decode_bytes_with(b"", &eager).unwrap().is_empty());
+        assert_eq!(decode_bytes_projected_with(input, &[], &eager).unwrap(), vec![Vec::<Cow<[u8]>>::new(); expected.len()]);
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_multiple_empty_rows() {
-        let nsv = "a\n\n\n\n\nb\n";
-        let result = decode(nsv);
+    fn test_decode_options_thread_pool() {
+        let pool = std::sync::Arc::new(rayon::ThreadPoolBuilder::new().num_threads(3).build().unwrap());
+        let data: Vec<Vec<String>> = (0..20_000).map(|i| vec![format!("r{}", i), format!("x\n{}", i)]).collect();
+        let encoded = encode(&data);
+
+        let options = DecodeOptions::new().parallel_threshold(0).thread_pool(pool);
+        assert_eq!(decode_with(&encoded, &options).unwrap(), data);
         assert_eq!(
-            result,
-            vec![
-                vec!["a".to_string()],
-                vec![],
-                vec![],
-                vec![],
-                vec!["b".to_string()],
-            ]
+            owned(decode_bytes_projected_with(encoded.as_bytes(), &[1], &options).unwrap()),
+            data.iter().map(|row| vec![row[1].as_bytes().to_vec()]).collect::<Vec<_>>(),
         );
     }
 
-    #[test]
-    fn test_roundtrip() {
-        let original = vec![
-            vec!["col1".to_string(), "col2".to_string()],
-            vec!["a".to_string(), "b".to_string()],
-            vec!["".to_string(), "value\\with\\backslash".to_string()],
-            vec!["multi\nline".to_string(), "normal".to_string()],
-        ];
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_decode_pieces_sized_for_thread_pool() {
+        let threads = rayon::current_num_threads() + 2;
+        let pool = std::sync::Arc::new(rayon::ThreadPoolBuilder::new().num_threads(threads).build().unwrap());
+        let input = b"x\n\n".repeat(threads * CHUNKS_PER_THREAD * MIN_CHUNK_SIZE / 3 + 1);
+        let options = DecodeOptions::new().thread_pool(pool);
+        let pieces = options.decode_pieces(&input, |piece| piece.len(), |_| 0).unwrap();
+        assert_eq!(pieces.len(), threads * CHUNKS_PER_THREAD);
+        assert_eq!(pieces.iter().sum::<usize>(), input.len());
+    }
+
+    fn progress_input() -> (Vec<Vec<String>>, String) {
+        // Over two tracking intervals, so progress is reported more than once
+        let data: Vec<Vec<String>> = (0..600_000).map(|i| vec![format!("row{}", i), format!("v\\{}", i)]).collect();
+        let encoded = encode(&data);
+        assert!(encoded.len() > 2 * TRACKING_INTERVAL);
+        (data, encoded)
+    }
+
+    /// Sequential options, and parallel ones on a multi-threaded pool.
+    fn tracked_options() -> Vec<DecodeOptions> {
+        let sequential = DecodeOptions::new().sequential(true);
+        #[cfg(feature = "parallel")]
+        {
+            let pool = rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap();
+            vec![sequential, DecodeOptions::new().parallel_threshold(0).thread_pool(Arc::new(pool))]
+        }
+        #[cfg(not(feature = "parallel"))]
+        vec![sequential]
+    }
+
+    #[test]
+    fn test_decode_progress() {
+        let (data, encoded) = progress_input();
+        for options in tracked_options() {
+            let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = Arc::clone(&reports);
+            let options = options.on_progress(move |progress| sink.lock().unwrap().push(progress));
+
+            assert_eq!(decode_with(&encoded, &options).unwrap(), data);
+            let reports = reports.lock().unwrap();
+            assert!(reports.len() > 1);
+            let last = reports.iter().max_by_key(|p| p.bytes).unwrap();
+            assert_eq!(*last, Progress { bytes: encoded.len(), rows: data.len() });
+        }
+    }
+
+    #[test]
+    fn test_decode_cancel() {
+        let (_, encoded) = progress_input();
+
+        let token = CancelToken::new();
+        token.cancel();
+        let options = DecodeOptions::new().cancel_token(token.clone());
+        assert_eq!(decode_bytes_with(encoded.as_bytes(), &options), Err(Cancelled));
+        assert_eq!(decode_bytes_projected_with(encoded.as_bytes(), &[0], &options), Err(Cancelled));
+
+        // Cancelled from the progress callback, after the first report
+        for options in tracked_options() {
+            let sequential = options.sequential;
+            let token = CancelToken::new();
+            let trigger = token.clone();
+            let reports = Arc::new(AtomicUsize::new(0));
+            let count = Arc::clone(&reports);
+            let options = options.cancel_token(token).on_progress(move |_| {
+                count.fetch_add(1, Ordering::Relaxed);
+                trigger.cancel();
+            });
+            assert_eq!(decode_with(&encoded, &options), Err(Cancelled));
+            if sequential {
+                assert_eq!(reports.load(Ordering::Relaxed), 1);
+            }
+        }
+
+        // An untriggered token changes nothing
+        let options = DecodeOptions::new().cancel_token(CancelToken::new());
+        assert_eq!(owned(decode_bytes_with(b"a\n\nb\n", &options).unwrap()), owned(decode_bytes(b"a\n\nb\n")));
+    }
+
+    #[test]
+    fn test_table_progress_and_cancel() {
+        let (data, encoded) = progress_input();
+        for options in tracked_options() {
+            let reports = Arc::new(std::sync::Mutex::new(Vec::new()));
+            let sink = Arc::clone(&reports);
+            let tracked = options.clone().on_progress(move |progress| sink.lock().unwrap().push(progress));
+
+            let table = Table::decode_with(encoded.as_bytes(), &tracked).unwrap();
+            assert_eq!(table.len(), data.len());
+            let reports = reports.lock().unwrap();
+            assert!(reports.len() > 1);
+            let last = reports.iter().max_by_key(|p| p.bytes).unwrap();
+            assert_eq!(*last, Progress { bytes: encoded.len(), rows: data.len() });
+
+            let token = CancelToken::new();
+            let trigger = token.clone();
+            let cancelling = options.cancel_token(token).on_progress(move |_| trigger.cancel());
+            assert_eq!(Table::decode_with(encoded.as_bytes(), &cancelling), Err(Cancelled));
+        }
+    }
+
+    #[test]
+    fn test_rows_match_decode() {
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 7) % 300);
+            let rows: Vec<Vec<Vec<u8>>> = rows(&input).map(|row| row.iter().map(Cow::into_owned).collect()).collect();
+            assert_eq!(rows, decode_reference(&input), "seed {}", seed);
+        }
+    }
+
+    #[test]
+    fn test_decode_bytes_range() {
+        for seed in 0..200 {
+            let input = random_input(seed, (seed as usize * 7) % 200);
+            let all = decode_reference(&input);
+            for skip in 0..all.len() + 2 {
+                for take in [0, 1, 2, 5, usize::MAX] {
+                    let expected: Vec<_> = all.iter().skip(skip).take(take).cloned().collect();
+                    assert_eq!(owned(decode_bytes_range(&input, skip, take)), expected, "seed {} {}+{}", seed, skip, take);
+                }
+            }
+        }
+    }
+
+    fn shape_reference(rows: &[Vec<Vec<u8>>]) -> Shape {
+        Shape {
+            rows: rows.len(),
+            min_cols: rows.iter().map(Vec::len).min().unwrap_or(0),
+            max_cols: rows.iter().map(Vec::len).max().unwrap_or(0),
+            cells: rows.iter().map(Vec::len).sum(),
+        }
+    }
 
-        let encoded = encode(&original);
-        let decoded = decode(&encoded);
-        assert_eq!(original, decoded);
+    /// Yields its input a few bytes per `read`.
+    struct Trickle<'a>(&'a [u8], usize);
+
+    impl io::Read for Trickle<'_> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = self.1.min(buf.len()).min(self.0.len());
+            buf[..n].copy_from_slice(&self.0[..n]);
+            self.0 = &self.0[n..];
+            Ok(n)
+        }
     }
 
     #[test]
-    fn test_unrecognized_escape() {
-        let nsv = "\\x41\\t\\r\n";
-        let result = decode(nsv);
-        assert_eq!(result, vec![vec!["\\x41\\t\\r".to_string()],]);
+    fn test_shape_and_count_rows() {
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 7) % 300);
+            let expected = shape_reference(&decode_reference(&input));
+            assert_eq!(shape(&input), expected, "seed {}", seed);
+            assert_eq!(count_rows(&input), expected.rows, "seed {}", seed);
+            for piece in [1, 2, 5] {
+                assert_eq!(shape_reader(Trickle(&input, piece)).unwrap(), expected, "seed {} piece {}", seed, piece);
+            }
+        }
+        assert_eq!(shape(b""), Shape::default());
+        assert_eq!(shape(b"\n"), Shape { rows: 1, min_cols: 0, max_cols: 0, cells: 0 });
     }
 
     #[test]
-    fn test_dangling_backslash() {
-        let nsv = "text\\\n";
-        let result = decode(nsv);
-        assert_eq!(result, vec![vec!["text".to_string()],]);
+    fn test_shape_large() {
+        let data: Vec<Vec<String>> = (0..60_000).map(|i| (0..i % 7).map(|j| format!("{}\n{}", i, j)).collect()).collect();
+        let encoded = encode(&data);
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
+
+        let expected = Shape { rows: data.len(), min_cols: 0, max_cols: 6, cells: data.iter().map(Vec::len).sum() };
+        assert_eq!(shape(encoded.as_bytes()), expected);
+        assert_eq!(shape_reader(encoded.as_bytes()).unwrap(), expected);
+        assert_eq!(count_rows(encoded.as_bytes()), data.len());
+
+        let points = boundaries::split_points(encoded.as_bytes(), 13);
+        let chunked = points.windows(2).map(|w| shape_sequential(&encoded.as_bytes()[w[0]..w[1]]));
+        assert_eq!(chunked.fold(Shape::default(), Shape::merge), expected);
     }
 
     #[test]
-    fn test_empty_input() {
-        let result = decode("");
-        assert_eq!(result, Vec::<Vec<String>>::new());
+    fn test_projected_ragged() {
+        for seed in 0..200 {
+            let input = random_input(seed, (seed as usize * 11) % 300);
+            for columns in [&[0, 2][..], &[3, 1, 0], &[]] {
+                let expected: Vec<(Vec<Option<Vec<u8>>>, usize)> = decode_reference(&input)
+                    .into_iter()
+                    .map(|row| (columns.iter().map(|&c| row.get(c).cloned()).collect(), row.len()))
+                    .collect();
+                let to_owned = |rows: Vec<ProjectedRow>| -> Vec<(Vec<Option<Vec<u8>>>, usize)> {
+                    rows.into_iter()
+                        .map(|row| (row.cells.into_iter().map(|c| c.map(Cow::into_owned)).collect(), row.width))
+                        .collect()
+                };
+                assert_eq!(to_owned(decode_bytes_projected_ragged(&input, columns)), expected, "seed {}", seed);
+
+                #[cfg(feature = "parallel")]
+                for num_chunks in [2, 5, 40] {
+                    let sink = RaggedProjection(Projection::new(columns));
+                    let chunked = decode_rows_chunked(&sink, &input, num_chunks, &Tracker::default()).unwrap();
+                    assert_eq!(to_owned(chunked), expected, "seed {} chunks {}", seed, num_chunks);
+                }
+            }
+        }
+    }
+
+    /// Selection by definition, straight from the decoded row.
+    fn select_reference(row: &[Vec<u8>], selector: &ColumnSelector) -> Vec<Vec<u8>> {
+        let mut out = Vec::new();
+        selector.for_each(row.len(), |col| out.push(col.map_or_else(Vec::new, |col| row[col].clone())));
+        out
+    }
+
+    fn selectors() -> Vec<ColumnSelector> {
+        vec![
+            ColumnSelector::all(),
+            ColumnSelector::new(),
+            ColumnSelector::new().index(2).index(0),
+            ColumnSelector::new().index(-1).index(0),
+            ColumnSelector::new().range(1..),
+            ColumnSelector::new().range(-2..).index(5),
+            ColumnSelector::all().exclude(1).exclude(-1),
+            ColumnSelector::new().range(0..4).exclude_range(2..),
+        ]
     }
 
     #[test]
-    fn test_no_trailing_newline() {
-        let nsv = "a\nb";
-        let result = decode(nsv);
-        assert_eq!(result, vec![vec!["a".to_string(), "b".to_string()],]);
+    fn test_decode_bytes_selected() {
+        for seed in 0..200 {
+            let input = random_input(seed, (seed as usize * 11) % 300);
+            let decoded = decode_reference(&input);
+            for selector in selectors() {
+                let expected: Vec<_> = decoded.iter().map(|row| select_reference(row, &selector)).collect();
+                assert_eq!(owned(decode_bytes_selected(&input, &selector)), expected, "seed {} {:?}", seed, selector);
+
+                // The reader leaves an unterminated last row pending
+                let streamed: Vec<_> = Reader::new(Cursor::new(&input)).map(|r| select_reference(&r.unwrap(), &selector)).collect();
+                let selected: Vec<_> = Reader::new(Cursor::new(&input)).select(selector.clone()).map(|r| r.unwrap()).collect();
+                assert_eq!(selected, streamed, "seed {} {:?}", seed, selector);
+
+                #[cfg(feature = "parallel")]
+                for num_chunks in [2, 5, 40] {
+                    let chunked = decode_rows_chunked(&RawCells, &input, num_chunks, &Tracker::default()).unwrap();
+                    let chunked: Vec<Vec<Vec<u8>>> = chunked
+                        .iter()
+                        .map(|row| {
+                            let row: Vec<Vec<u8>> = row.iter().map(|&(raw, escaped)| cell(raw, escaped).into_owned()).collect();
+                            select_reference(&row, &selector)
+                        })
+                        .collect();
+                    assert_eq!(chunked, expected, "seed {} chunks {}", seed, num_chunks);
+                }
+            }
+        }
     }
 
     #[test]
-    fn test_only_empty_rows() {
-        let nsv = "\n\n\n\n";
-        let result = decode(nsv);
-        assert_eq!(
-            result,
-            vec![
-                Vec::<String>::new(),
-                Vec::<String>::new(),
-                Vec::<String>::new(),
-                Vec::<String>::new(),
-            ]
-        );
+    fn test_projection_huge_indices() {
+        // Lookups cost the columns projected, not their largest index
+        let input = b"a\nb\n\nc\n\n";
+        let empty: &[u8] = b"";
+        for far in [1 << 40, usize::MAX] {
+            assert_eq!(decode_bytes_projected(input, &[far, 0]), [[empty, b"a"], [empty, b"c"]]);
+            assert_eq!(decode_bytes_projected_ragged(input, &[1, far])[0].cells, [Some(Cow::Borrowed(&b"b"[..])), None]);
+            assert_eq!(decode_bytes_projected_with_spans(input, &[far])[1], [None]);
+            assert_eq!(decode_bytes_filtered(input, &[far, 1], &[far], |_| true), [[empty, b"b"], [empty, empty]]);
+            let mut interner = Interner::new();
+            let rows = decode_bytes_projected_interned(input, &[0, far], &mut interner);
+            assert_eq!(interner.resolve(rows[1][1]), b"");
+        }
+        let selector = ColumnSelector::new().index(isize::MAX).index(0).index(1 << 40);
+        assert_eq!(decode_bytes_selected(input, &selector), [[empty, b"a", empty], [empty, b"c", empty]]);
+        assert_eq!(decode_columns(input, &selector)[1], [&b"a"[..], b"c"]);
+        let selected: Vec<Vec<Vec<u8>>> = Reader::new(Cursor::new(input)).select(selector).map(|r| r.unwrap()).collect();
+        assert_eq!(selected, [[vec![], b"a".to_vec(), vec![]], [vec![], b"c".to_vec(), vec![]]]);
     }
 
     #[test]
-    fn test_starts_with_empty_row() {
-        let nsv = "\n\nfirst\n";
-        let result = decode(nsv);
+    fn test_selected_static_matches_dynamic() {
+        // The same columns, once as indices and once through an open range
+        let input = b"a\nb\nc\n\nd\n\n\\\ne\\nf\ng\nh\n\n";
+        let listed = ColumnSelector::from(&[1, 2, 3][..]);
+        let open = ColumnSelector::new().range(1..).exclude_range(4..);
+        assert!(listed.static_columns().is_some() && open.static_columns().is_none());
+        assert_eq!(decode_bytes_selected(input, &listed), decode_bytes_projected(input, &[1, 2, 3]));
         assert_eq!(
-            result,
-            vec![
-                Vec::<String>::new(),
-                Vec::<String>::new(),
-                vec!["first".to_string()],
-            ]
+            decode_bytes_selected(input, &open),
+            [vec![&b"b"[..], b"c"], vec![], vec![b"e\nf", b"g", b"h"]]
         );
     }
 
     #[test]
-    fn test_large_file() {
-        // Generate ~10MB of data to verify parallel path is exercised
-        // (needs to exceed PARALLEL_THRESHOLD of 64KB)
-        let large_data: Vec<Vec<String>> = (0..100_000)
-            .map(|i| vec![format!("row{}", i), format!("data{}", i)])
-            .collect();
-
-        let encoded = encode(&large_data);
+    fn test_projected_take_selectors() {
+        let selectors = [
+            ColumnSelector::new().index(-1).index(1),
+            ColumnSelector::new().range(-2..),
+            ColumnSelector::all().exclude(0),
+            ColumnSelector::from(&[2, 0][..]),
+            ColumnSelector::new(),
+            ColumnSelector::all().exclude_range(..2),
+        ];
+        // Selecting nothing still yields every row, whichever way it is spelled
+        let input = b"a\nb\n\nc\n\n";
+        let none = vec![Vec::<Cow<[u8]>>::new(); 2];
+        assert_eq!(decode_bytes_projected(input, &[]), none);
+        for selector in &selectors[4..] {
+            assert_eq!(decode_bytes_projected(input, selector), none);
+            assert_eq!(decode_bytes_selected(input, selector), none);
+        }
+        let mut interner = Interner::new();
+        assert_eq!(decode_bytes_projected_interned(input, &[], &mut interner), vec![Vec::<Symbol>::new(); 2]);
+
+        for seed in 0..100 {
+            let input = random_input(seed, (seed as usize * 13) % 200);
+            let reference = decode_reference(&input);
+            let spans = decode_bytes_with_spans(&input);
+            for selector in &selectors {
+                let selected = decode_bytes_selected(&input, selector);
+                assert_eq!(decode_bytes_projected(&input, selector), selected, "seed {} {:?}", seed, selector);
+                let short_first = |row: &Vec<Vec<u8>>| row.first().map_or(0, Vec::len) < 2;
+                let expected: Vec<_> = selected.iter().zip(&reference).filter(|(_, row)| short_first(row)).map(|(cells, _)| cells.clone()).collect();
+                let filtered = decode_bytes_filtered(&input, selector, &[0], |keys| keys[0].len() < 2);
+                assert_eq!(filtered, expected, "seed {} {:?}", seed, selector);
+
+                let ragged = decode_bytes_projected_ragged(&input, selector);
+                let with_spans = decode_bytes_projected_with_spans(&input, selector);
+                assert_eq!(ragged.len(), reference.len());
+                for (r, row) in reference.iter().enumerate() {
+                    let mut expected = Vec::new();
+                    selector.for_each(row.len(), |col| expected.push(col));
+                    let cells: Vec<Option<&[u8]>> = expected.iter().map(|col| col.map(|c| &row[c][..])).collect();
+                    let got: Vec<Option<&[u8]>> = ragged[r].cells.iter().map(|c| c.as_deref()).collect();
+                    assert_eq!((got, ragged[r].width), (cells, row.len()), "seed {} {:?}", seed, selector);
+                    let spanned: Vec<Option<&SpannedCell>> = expected.iter().map(|col| col.map(|c| &spans[r][c])).collect();
+                    assert_eq!(with_spans[r].iter().map(Option::as_ref).collect::<Vec<_>>(), spanned, "seed {} {:?}", seed, selector);
+                }
+            }
+        }
+    }
 
-        // Verify it's large enough to trigger parallel parsing
+    #[test]
+    fn test_decode_bytes_selected_large() {
+        let data: Vec<Vec<String>> = (0..60_000).map(|i| (0..i % 5).map(|j| format!("{}\\{}", i, j)).collect()).collect();
+        let encoded = encode(&data);
         assert!(encoded.len() > PARALLEL_THRESHOLD);
 
-        let decoded = decode(&encoded);
-        assert_eq!(large_data, decoded);
+        let selector = ColumnSelector::new().index(-1).range(..-1);
+        let expected: Vec<Vec<Vec<u8>>> = data
+            .iter()
+            .map(|row| {
+                let mut cells: Vec<Vec<u8>> = row.iter().map(|c| c.as_bytes().to_vec()).collect();
+                let last = cells.pop().unwrap_or_default();
+                cells.insert(0, last);
+                cells
+            })
+            .collect();
+        assert_eq!(owned(decode_bytes_selected(encoded.as_bytes(), &selector)), expected);
+        let sequential = DecodeOptions::new().sequential(true);
+        assert_eq!(owned(decode_bytes_selected_with(encoded.as_bytes(), &selector, &sequential).unwrap()), expected);
+
+        #[cfg(feature = "parallel")]
+        {
+            let pool = Arc::new(rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap());
+            let options = DecodeOptions::new().thread_pool(pool);
+            assert_eq!(owned(decode_bytes_selected_with(encoded.as_bytes(), &selector, &options).unwrap()), expected);
+        }
     }
 
     #[test]
-    fn test_parallel_with_empty_rows() {
-        // Test parallel path with empty rows mixed in
-        let mut data = Vec::new();
+    fn test_bytes_reader_select_resumable() {
+        let s = GrowableStream::new();
+        let mut r = Reader::new(&s).select(ColumnSelector::new().index(-1).index(0));
 
-        for i in 0..40_000 {
-            data.push(vec![format!("value{}", i)]);
+        s.append(b"a\\n\nb\nc\\\\");
+        assert_eq!(r.next_row().unwrap(), None);
+        // Selected cells wait escaped for the row's width; others are dropped
+        assert_eq!(r.partial_row(), [b"a\\n".to_vec(), b"b".to_vec()]);
+        s.append(b"\n\n\n");
+        assert_eq!(r.next_row().unwrap(), Some(vec![b"c\\".to_vec(), b"a\n".to_vec()]));
+        assert_eq!(r.next_row().unwrap(), Some(vec![vec![], vec![]]));
 
-            if i % 100 == 0 {
-                data.push(vec![]);
+        let mut r = Reader::new(Cursor::new(b"a\nb\nc\nd\n\n")).select(ColumnSelector::new().index(1));
+        assert_eq!(r.next_row().unwrap(), Some(vec![b"b".to_vec()]));
+    }
+
+    /// Column-major by definition: the selected rows transposed, short ones
+    /// padded with empty cells.
+    fn columns_reference(rows: &[Vec<Vec<u8>>], selector: &ColumnSelector) -> Vec<Vec<Vec<u8>>> {
+        let selected: Vec<_> = rows.iter().map(|row| select_reference(row, selector)).collect();
+        let width = selected.iter().map(Vec::len).max().unwrap_or(0).max(selector.static_columns().map_or(0, |c| c.len()));
+        (0..width).map(|k| selected.iter().map(|row| row.get(k).cloned().unwrap_or_default()).collect()).collect()
+    }
+
+    #[test]
+    fn test_decode_columns() {
+        for seed in 0..200 {
+            let input = random_input(seed, (seed as usize * 11) % 300);
+            let decoded = decode_reference(&input);
+            for selector in selectors() {
+                let expected = columns_reference(&decoded, &selector);
+                assert_eq!(owned(decode_columns(&input, &selector)), expected, "seed {} {:?}", seed, selector);
+
+                #[cfg(feature = "parallel")]
+                for num_chunks in [2, 5, 40] {
+                    let width = selector.static_columns().map_or(0, |c| c.len());
+                    let points = boundaries::split_points(&input, num_chunks);
+                    let chunks = points.windows(2).map(|w| columns_chunk(&selector, width, &input[w[0]..w[1]])).collect();
+                    assert_eq!(owned(concat_columns(chunks, width)), expected, "seed {} chunks {}", seed, num_chunks);
+                }
             }
         }
 
+        let listed = ColumnSelector::from(&[0, 3][..]);
+        assert_eq!(decode_columns(b"", &listed), [Vec::<Cow<[u8]>>::new(), vec![]]);
+        assert!(decode_columns(b"", &ColumnSelector::all()).is_empty());
+        // The short middle row is padded under an open range
+        assert_eq!(decode_columns(b"a\nb\n\nc\n\nd\ne\n\n", &ColumnSelector::all()), [[&b"a"[..], b"c", b"d"], [b"b", b"", b"e"]]);
+    }
+
+    #[test]
+    fn test_decode_columns_large() {
+        let data: Vec<Vec<String>> = (0..60_000).map(|i| (0..i % 5).map(|j| format!("{}\\{}", i, j)).collect()).collect();
         let encoded = encode(&data);
         assert!(encoded.len() > PARALLEL_THRESHOLD);
 
-        let decoded = decode(&encoded);
-        assert_eq!(data, decoded);
+        let rows: Vec<Vec<Vec<u8>>> = data.iter().map(|row| row.iter().map(|c| c.as_bytes().to_vec()).collect()).collect();
+        for selector in [ColumnSelector::all(), ColumnSelector::new().index(-1).index(2), ColumnSelector::from(&[4, 0][..])] {
+            assert_eq!(owned(decode_columns(encoded.as_bytes(), &selector)), columns_reference(&rows, &selector), "{:?}", selector);
+        }
     }
 
     #[test]
-    fn test_parallel_with_escape_sequences() {
-        // Test parallel path with cells containing escape sequences
-        let mut data = Vec::new();
+    fn test_decode_bytes_with_spans() {
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 7) % 300);
+            let rows = decode_bytes_with_spans(&input);
+            let values: Vec<Vec<Vec<u8>>> = rows.iter().map(|row| row.iter().map(|c| c.value.to_vec()).collect()).collect();
+            assert_eq!(values, decode_reference(&input), "seed {}", seed);
+
+            for cell in rows.iter().flatten() {
+                let raw = &input[cell.span.clone()];
+                assert_eq!(unescape_bytes(raw), cell.value, "seed {}", seed);
+                // A whole line: from a line start to its LF or the end of input
+                assert!(cell.span.start == 0 || input[cell.span.start - 1] == b'\n', "seed {}", seed);
+                assert!(input.get(cell.span.end).is_none_or(|&b| b == b'\n'), "seed {}", seed);
+                assert_eq!(cell.line, 1 + memchr::memchr_iter(b'\n', &input[..cell.span.start]).count(), "seed {}", seed);
+            }
 
-        for i in 0..20_000 {
-            data.push(vec![
-                format!("Line 1\nLine 2 {}", i),
-                format!("Backslash: \\ {}", i),
-                "".to_string(),
-            ]);
-        }
+            for columns in [&[1, 0][..], &[3], &[]] {
+                let projected = decode_bytes_projected_with_spans(&input, columns);
+                assert_eq!(projected.len(), decode_bytes_projected(&input, columns).len(), "seed {} columns {:?}", seed, columns);
+                let expected: Vec<Vec<Option<&SpannedCell>>> = rows.iter().map(|row| columns.iter().map(|&c| row.get(c)).collect()).collect();
+                let projected: Vec<Vec<Option<&SpannedCell>>> = projected.iter().map(|row| row.iter().map(Option::as_ref).collect()).collect();
+                assert_eq!(projected, expected, "seed {} columns {:?}", seed, columns);
+            }
 
-        let encoded = encode(&data);
-        assert!(encoded.len() > PARALLEL_THRESHOLD);
+            #[cfg(feature = "parallel")]
+            for num_chunks in [2, 5, 40] {
+                let sink = Spans { input: &input[..], projection: () };
+                let chunked = decode_rows_chunked(&sink, &input, num_chunks, &Tracker::default()).unwrap();
+                assert_eq!(chunked, decode_rows(&sink, &input), "seed {} chunks {}", seed, num_chunks);
 
-        let decoded = decode(&encoded);
-        assert_eq!(data, decoded);
+                let sink = Spans { input: &input[..], projection: Projection::new(&[2, 0]) };
+                let chunked = decode_rows_chunked(&sink, &input, num_chunks, &Tracker::default()).unwrap();
+                assert_eq!(chunked, decode_rows(&sink, &input), "seed {} chunks {}", seed, num_chunks);
+            }
+        }
     }
 
-    // ── Byte-level tests ──
-
     #[test]
-    fn test_bytes_roundtrip() {
-        let original: Vec<Vec<Vec<u8>>> = vec![
-            vec![b"col1".to_vec(), b"col2".to_vec()],
-            vec![b"a".to_vec(), b"b".to_vec()],
-            vec![b"".to_vec(), b"value\\with\\backslash".as_slice().to_vec()],
-            vec![b"multi\nline".to_vec(), b"normal".to_vec()],
-        ];
+    fn test_spans_match_check_positions() {
+        let input = b"ok\nbad\\q\n\n\\\ndangling\\";
+        let rows = decode_bytes_with_spans(input);
+        for warning in check(input) {
+            if warning.kind == WarningKind::NoTerminalLf {
+                continue;
+            }
+            let cell = rows.iter().flatten().find(|c| c.span.contains(&warning.pos)).unwrap();
+            assert_eq!(cell.line, warning.line, "{:?}", warning);
+        }
+        assert_eq!(rows[1][1].span, 12..21);
+    }
 
-        let encoded = encode_bytes(&original);
-        let decoded = owned(decode_bytes(&encoded));
-        assert_eq!(original, decoded);
+    #[test]
+    fn test_decode_bytes_filtered() {
+        let project = |row: &Vec<Vec<u8>>, columns: &[usize]| -> Vec<Vec<u8>> {
+            columns.iter().map(|&c| row.get(c).cloned().unwrap_or_default()).collect()
+        };
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 7) % 300);
+            for (columns, keys) in [(&[0, 2][..], &[1][..]), (&[1], &[0, 3]), (&[2, 0], &[2]), (&[0], &[])] {
+                // Keep rows whose key cells are all short
+                let keep = |cells: &[Vec<u8>]| cells.iter().all(|c| c.len() < 2);
+                let expected: Vec<Vec<Vec<u8>>> = decode_reference(&input)
+                    .iter()
+                    .filter(|row| keep(&project(row, keys)))
+                    .map(|row| project(row, columns))
+                    .collect();
+                let filtered = decode_bytes_filtered(&input, columns, keys, |cells| cells.iter().all(|c| c.len() < 2));
+                assert_eq!(owned(filtered), expected, "seed {} columns {:?} keys {:?}", seed, columns, keys);
+            }
+        }
+        assert_eq!(decode_bytes_filtered(b"a\n\nb\n\n", &[], &[0], |keys| keys[0] == &b"a"[..]), [Vec::<Cow<[u8]>>::new()]);
+        // A projected key cell is the one the predicate saw, however often it is picked
+        let twice = ColumnSelector::new().index(0).index(-1).index(0);
+        assert_eq!(decode_bytes_filtered(b"a\\nb\nc\n\n", &twice, &[0], |_| true), [[&b"a\nb"[..], b"c", b"a\nb"]]);
     }
 
     #[test]
-    fn test_bytes_non_utf8() {
-        // Latin-1 bytes with values > 0x7F — not valid UTF-8
-        let cell1: Vec<u8> = vec![0xC0, 0xE9, 0xF1]; // àéñ in Latin-1
-        let cell2: Vec<u8> = vec![0xFF, 0xFE, 0x80]; // arbitrary high bytes
-        let original: Vec<Vec<Vec<u8>>> = vec![vec![cell1.clone(), cell2.clone()]];
+    fn test_decode_bytes_filtered_large() {
+        let data: Vec<Vec<String>> = (0..100_000)
+            .map(|i| vec![format!("id{}", i), format!("{}", i % 97), format!("v\\{}\n", i)])
+            .collect();
+        let encoded = encode(&data);
+        assert!(encoded.len() > PARALLEL_THRESHOLD);
 
-        let encoded = encode_bytes(&original);
-        let decoded = owned(decode_bytes(&encoded));
-        assert_eq!(original, decoded);
+        let filtered = decode_bytes_filtered(encoded.as_bytes(), &[2, 0], &[1], |keys| keys[0] == &b"13"[..]);
+        let expected: Vec<Vec<Vec<u8>>> = data
+            .iter()
+            .filter(|row| row[1] == "13")
+            .map(|row| vec![row[2].as_bytes().to_vec(), row[0].as_bytes().to_vec()])
+            .collect();
+        assert_eq!(owned(filtered), expected);
     }
 
     #[test]
-    fn test_bytes_empty_cells_and_rows() {
-        let original: Vec<Vec<Vec<u8>>> = vec![
-            vec![b"a".to_vec(), b"".to_vec(), b"b".to_vec()],
-            vec![],
-            vec![b"".to_vec()],
-        ];
+    fn test_row_ref_access() {
+        let input = b"a\\\\\n\\\nb\\nc\n\n\nd\n\\\n";
+        let rows: Vec<RowRef> = rows(input).collect();
+        assert_eq!(rows.len(), 3);
 
-        let encoded = encode_bytes(&original);
-        let decoded = owned(decode_bytes(&encoded));
-        assert_eq!(original, decoded);
+        let row = rows[0];
+        assert_eq!(row.len(), 3);
+        assert_eq!(row.raw(), b"a\\\\\n\\\nb\\nc\n");
+        assert_eq!(row.get(0).unwrap(), &b"a\\"[..]);
+        assert_eq!(row.get(1).unwrap(), &b""[..]);
+        assert_eq!(row.get(2).unwrap(), &b"b\nc"[..]);
+        assert!(row.get(3).is_none());
+        assert!(matches!(row.get(0), Some(Cow::Owned(_))));
+
+        assert!(rows[1].is_empty());
+        assert_eq!(rows[1].len(), 0);
+        assert_eq!(rows[1].iter().count(), 0);
+
+        assert_eq!(rows[2].into_iter().collect::<Vec<_>>(), [&b"d"[..], b""]);
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_bytes_with_special_ascii_bytes() {
-        // Verify that only 0x0A (LF), 0x5C (\), 0x6E (n) are structurally significant
-        let cell: Vec<u8> = (0u8..=255)
-            .filter(|&b| b != b'\n' && b != b'\\')
-            .collect();
-        let original = vec![vec![cell.clone()]];
-
-        let encoded = encode_bytes(&original);
-        let decoded = owned(decode_bytes(&encoded));
-        assert_eq!(original, decoded);
+    fn test_par_rows_match_decode() {
+        for seed in 0..200 {
+            let input = random_input(seed, (seed as usize * 11) % 400);
+            let expected = decode_reference(&input);
+            for num_chunks in [1, 2, 3, 7, 50] {
+                let rows: Vec<Vec<Vec<u8>>> = par_rows_chunked(&input, num_chunks)
+                    .map(|row| row.into_iter().map(Cow::into_owned).collect())
+                    .collect();
+                assert_eq!(rows, expected, "seed {} chunks {}", seed, num_chunks);
+
+                let starts = row_starts(&input, num_chunks);
+                assert_eq!(starts.len(), expected.len() + 1, "seed {} chunks {}", seed, num_chunks);
+                let spans: Vec<Vec<Vec<u8>>> = starts
+                    .windows(2)
+                    .map(|w| row_at(&input, w[0], w[1]).iter().map(Cow::into_owned).collect())
+                    .collect();
+                assert_eq!(spans, expected, "seed {} chunks {}", seed, num_chunks);
+            }
+        }
     }
 
+    #[cfg(feature = "parallel")]
     #[test]
-    fn test_decode_bytes_matches_decode_str() {
-        // Confirm decode(s) and decode_bytes(s.as_bytes()) produce equivalent structures
-        let inputs = vec![
-            "col1\ncol2\n\na\nb\n\nc\nd\n",
-            "a\n\\\nb\n\n\\\nc\n\\\n",
-            "Line 1\\nLine 2\nBackslash: \\\\\n",
-            "first\n\n\n\nsecond\n",
-            "",
-            "\n\n\n\n",
-        ];
+    fn test_par_rows_large() {
+        let data: Vec<Vec<String>> = (0..100_000).map(|i| vec![format!("{}", i), format!("x\\n{}", i)]).collect();
+        let encoded = encode(&data);
 
-        for input in inputs {
-            let str_result = decode(input);
-            let byte_result = decode_bytes(input.as_bytes());
+        let sum: usize = par_rows(encoded.as_bytes())
+            .map(|row| std::str::from_utf8(&row[0]).unwrap().parse::<usize>().unwrap())
+            .sum();
+        assert_eq!(sum, (0..100_000).sum::<usize>());
 
-            // Convert byte result to strings for comparison
-            let byte_as_str: Vec<Vec<String>> = byte_result
-                .into_iter()
-                .map(|row| {
-                    row.into_iter()
-                        .map(|cell| String::from_utf8(cell.into_owned()).unwrap())
-                        .collect()
-                })
-                .collect();
+        let indexed: Vec<(usize, Vec<Cow<[u8]>>)> = par_rows_indexed(encoded.as_bytes()).enumerate().collect();
+        assert_eq!(indexed.len(), data.len());
+        for (i, row) in indexed {
+            assert_eq!(row, vec![data[i][0].as_bytes(), data[i][1].as_bytes()]);
+        }
+        assert_eq!(par_rows_indexed(b"").count(), 0);
+        assert_eq!(par_rows(b"").count(), 0);
+    }
 
-            assert_eq!(str_result, byte_as_str, "mismatch for input: {:?}", input);
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_chunk_shape_matches_line_scan() {
+        for seed in 0..300 {
+            let input = random_input(seed, (seed as usize * 13) % 200);
+            let mut expected = ChunkShape::default();
+            let mut line_start = 0;
+            for (i, &b) in input.iter().enumerate() {
+                if b == b'\n' {
+                    if i == line_start {
+                        expected.ends += 1;
+                        expected.tail = 0;
+                    } else if expected.ends == 0 {
+                        expected.head += 1;
+                    } else {
+                        expected.tail += 1;
+                    }
+                    line_start = i + 1;
+                }
+            }
+            if line_start < input.len() {
+                if expected.ends == 0 {
+                    expected.head += 1;
+                } else {
+                    expected.tail += 1;
+                }
+            }
+            let shape = ChunkShape::of(&input);
+            assert_eq!(
+                (shape.ends, shape.head, shape.tail),
+                (expected.ends, expected.head, expected.tail),
+                "input {:?}",
+                input
+            );
         }
     }
 
+    // ── Parallel encode ──
+
     #[test]
-    fn test_bytes_large_parallel() {
-        // Generate enough data to trigger parallel path
-        let large_data: Vec<Vec<Vec<u8>>> = (0..100_000)
-            .map(|i| {
-                vec![
-                    format!("row{}", i).into_bytes(),
-                    format!("data{}", i).into_bytes(),
-                ]
+    fn test_encode_parallel_matches_sequential() {
+        let data: Vec<Vec<String>> = (0..50_000)
+            .map(|i| match i % 4 {
+                0 => vec![],
+                1 => vec!["".to_string(), format!("back\\slash{}", i)],
+                2 => vec![format!("multi\nline{}", i)],
+                _ => vec![format!("row{}", i), format!("data{}", i)],
             })
             .collect();
 
-        let encoded = encode_bytes(&large_data);
+        let encoded = encode(&data);
         assert!(encoded.len() > PARALLEL_THRESHOLD);
+        assert_eq!(encoded.as_bytes(), encode_rows_sequential(&data));
+        assert_eq!(decode(&encoded), data);
 
-        let decoded = owned(decode_bytes(&encoded));
-        assert_eq!(large_data, decoded);
+        let bytes: Vec<Vec<Vec<u8>>> = data
+            .iter()
+            .map(|row| row.iter().map(|c| c.clone().into_bytes()).collect())
+            .collect();
+        assert_eq!(encode_bytes(&bytes), encoded.as_bytes());
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_encode_parallel_few_rows() {
+        // Fewer rows than threads, one of them large enough to cross the threshold
+        let data = vec![vec!["x\n".repeat(PARALLEL_THRESHOLD)], vec![], vec!["y".to_string()]];
+        assert_eq!(encode_rows_parallel(&data), encode_rows_sequential(&data));
+        assert_eq!(encode(&data).as_bytes(), encode_rows_sequential(&data));
     }
 
     // ── check() tests ──
@@ -1230,6 +3777,44 @@ mod tests {
         assert_eq!(r.next_row().unwrap(), Some(vec![b"hello".to_vec()]));
     }
 
+    #[test]
+    fn test_bytes_reader_skip_rows() {
+        for seed in 0..200 {
+            let input = random_input(seed, (seed as usize * 7) % 200);
+            let all: Vec<_> = Reader::new(Cursor::new(&input)).map(|r| r.unwrap()).collect();
+            for k in 0..all.len() + 2 {
+                for capacity in [1, 3, 64] {
+                    let mut r = Reader::from_buf_reader(io::BufReader::with_capacity(capacity, Cursor::new(&input)));
+                    assert_eq!(r.skip_rows(k).unwrap(), k.min(all.len()), "seed {} skip {}", seed, k);
+                    let rest: Vec<_> = r.map(|r| r.unwrap()).collect();
+                    assert_eq!(rest, all[k.min(all.len())..], "seed {} skip {}", seed, k);
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_bytes_reader_skip_rows_resumable() {
+        let s = GrowableStream::new();
+        let mut r = Reader::new(&s);
+
+        // Skipping drops a partially read row
+        s.append(b"a\nb");
+        assert_eq!(r.next_row().unwrap(), None);
+        assert_eq!(r.skip_rows(2).unwrap(), 0);
+        assert!(r.partial_row().is_empty() && r.partial_cell().is_empty());
+
+        // The cut-off row is finished by the next call, across a line split
+        s.append(b"\n");
+        assert_eq!(r.skip_rows(1).unwrap(), 0);
+        s.append(b"\nc\n\n\n");
+        assert_eq!(r.next_row().unwrap(), Some(vec![b"c".to_vec()]));
+        assert_eq!(r.skip_rows(5).unwrap(), 1); // the empty row
+        s.append(b"d\n\ne\n\n");
+        assert_eq!(r.skip_rows(1).unwrap(), 1);
+        assert_eq!(r.next_row().unwrap(), Some(vec![b"e".to_vec()]));
+    }
+
     // ── Reader ──
 
     #[test]
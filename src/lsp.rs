@@ -0,0 +1,691 @@
+//! A language server for NSV files, over JSON-RPC with LSP's framing.
+//!
+//! [`Server`] handles one decoded message at a time; [`run`] serves it over a
+//! pair of byte streams, and the `nsv-lsp` binary over stdio. It provides:
+//!
+//! - diagnostics: the warnings of [`check`], published whenever
+//!   a document is opened or changed;
+//! - quick fixes that rewrite them canonically without changing the decoded
+//!   rows: `\x` becomes `\\x`, a dangling backslash is removed, and an
+//!   unterminated last row gets its LFs;
+//! - hover: the row and column of a cell, counting from 0 as the decoders
+//!   do, and its unescaped value;
+//! - document symbols: one per row.
+//!
+//! Documents are synced incrementally into [`IncrementalDocument`]s, so a
+//! change only re-decodes the rows it touches. Warnings and line offsets are
+//! kept alongside and likewise redone only for the lines a change touches;
+//! what is left per change is moving the offsets after it and sending the
+//! document's full set of diagnostics, as the protocol has it. Positions count UTF-16 code
+//! units, or bytes when the client offers `utf-8`. Invalid UTF-8 counts one
+//! unit per byte.
+
+use std::collections::HashMap;
+use std::io::{self, BufRead, Read, Write};
+use std::ops::Range;
+
+use serde_json::{json, Value};
+
+use crate::{check, IncrementalDocument, Warning, WarningKind};
+
+const PARSE_ERROR: i64 = -32700;
+const INVALID_REQUEST: i64 = -32600;
+const METHOD_NOT_FOUND: i64 = -32601;
+const INVALID_PARAMS: i64 = -32602;
+
+/// Serve LSP messages read from `input`, writing replies to `output`, until
+/// the client sends `exit` or closes `input`.
+///
+/// Returns whether the client shut the server down before exiting, which
+/// the protocol maps to exit code 0.
+pub fn run(mut input: impl BufRead, mut output: impl Write) -> io::Result<bool> {
+    let mut server = Server::new();
+    while let Some(body) = read_message(&mut input)? {
+        let replies = match serde_json::from_slice::<Value>(&body) {
+            Ok(message) => server.handle(&message),
+            Err(e) => vec![json!({ "jsonrpc": "2.0", "id": null, "error": { "code": PARSE_ERROR, "message": e.to_string() } })],
+        };
+        for reply in &replies {
+            write_message(&mut output, reply)?;
+        }
+        output.flush()?;
+        if server.exited {
+            return Ok(server.shut_down);
+        }
+    }
+    Ok(false)
+}
+
+/// The body of the next message, or `None` at the end of `input`.
+fn read_message(input: &mut impl BufRead) -> io::Result<Option<Vec<u8>>> {
+    let mut length = None;
+    let mut header = String::new();
+    loop {
+        header.clear();
+        if input.read_line(&mut header)? == 0 {
+            return match length {
+                None => Ok(None),
+                Some(_) => Err(io::Error::new(io::ErrorKind::UnexpectedEof, "message cut off in its header")),
+            };
+        }
+        let header = header.trim_end();
+        if header.is_empty() {
+            break;
+        }
+        if let Some((name, value)) = header.split_once(':') {
+            if name.eq_ignore_ascii_case("Content-Length") {
+                let value = value.trim().parse().map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad Content-Length"))?;
+                length = Some(value);
+            }
+        }
+    }
+    let length = length.ok_or_else(|| io::Error::new(io::ErrorKind::InvalidData, "message without Content-Length"))?;
+    // Grow with what actually arrives rather than trust the header
+    let mut body = Vec::new();
+    input.take(length).read_to_end(&mut body)?;
+    if body.len() as u64 != length {
+        return Err(io::Error::new(io::ErrorKind::UnexpectedEof, "message cut off in its body"));
+    }
+    Ok(Some(body))
+}
+
+fn write_message(output: &mut impl Write, message: &Value) -> io::Result<()> {
+    let body = message.to_string();
+    write!(output, "Content-Length: {}\r\n\r\n{}", body.len(), body)
+}
+
+/// How positions count characters within a line.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Encoding {
+    Utf8,
+    Utf16,
+}
+
+#[derive(Debug)]
+struct Document {
+    nsv: IncrementalDocument,
+    version: Value,
+    /// Where each line of the text starts: 0, and after every LF.
+    line_starts: Vec<usize>,
+    /// [`check`]'s warnings over the text, in order.
+    warnings: Vec<Warning>,
+}
+
+impl Document {
+    fn new(text: &str, version: Value) -> Self {
+        let nsv = IncrementalDocument::new(text);
+        let line_starts = line_starts(nsv.text());
+        let warnings = check(nsv.text());
+        Document { nsv, version, line_starts, warnings }
+    }
+
+    fn lines(&self, encoding: Encoding) -> Lines<'_> {
+        Lines { text: self.nsv.text(), starts: &self.line_starts, encoding }
+    }
+
+    /// Replace the bytes in `range` with `replacement`.
+    ///
+    /// A warning depends only on the bytes of its line, its LF included, so
+    /// only the lines from the one holding the edit's start to the one holding
+    /// its end are checked again; the others keep their warnings, moved.
+    fn edit(&mut self, range: Range<usize>, replacement: &[u8]) {
+        let old_len = self.nsv.text().len();
+        self.nsv.edit(range.clone(), replacement);
+        let text = self.nsv.text();
+
+        // `from..to` in the new text was `from..old_to` in the old one
+        let first = self.line_starts.partition_point(|&start| start <= range.start) - 1;
+        let from = self.line_starts[first];
+        let end = range.start + replacement.len();
+        let to = memchr::memchr(b'\n', &text[end..]).map_or(text.len(), |at| end + at + 1);
+        let old_to = to + range.len() - replacement.len();
+
+        let last = self.line_starts.partition_point(|&start| start <= old_to);
+        let starts: Vec<usize> = memchr::memchr_iter(b'\n', &text[from..to]).map(|at| from + at + 1).collect();
+        let added_lines = starts.len();
+        let removed_lines = last - first - 1;
+        for start in &mut self.line_starts[last..] {
+            *start = *start - old_to + to;
+        }
+        self.line_starts.splice(first + 1..last, starts);
+
+        // The end of the text is `old_to` too when the edit reaches it
+        let lo = self.warnings.partition_point(|warning| warning.pos < from);
+        let hi = self.warnings.partition_point(|warning| warning.pos < old_to || (warning.pos == old_len && old_to == old_len));
+        for warning in &mut self.warnings[hi..] {
+            warning.pos = warning.pos - old_to + to;
+            warning.line = warning.line + added_lines - removed_lines;
+        }
+        let rechecked = check(&text[from..to]).into_iter().map(|warning| Warning { pos: warning.pos + from, line: warning.line + first, ..warning });
+        self.warnings.splice(lo..hi, rechecked);
+    }
+}
+
+/// The state of a language server: open documents and what was negotiated
+/// with the client.
+///
+/// ```
+/// use serde_json::json;
+///
+/// let mut server = nsv::lsp::Server::new();
+/// server.handle(&json!({ "jsonrpc": "2.0", "id": 1, "method": "initialize", "params": { "capabilities": {} } }));
+/// let replies = server.handle(&json!({
+///     "jsonrpc": "2.0",
+///     "method": "textDocument/didOpen",
+///     "params": { "textDocument": { "uri": "file:///a.nsv", "languageId": "nsv", "version": 1, "text": "a\\x\n\n" } },
+/// }));
+/// assert_eq!(replies[0]["method"], "textDocument/publishDiagnostics");
+/// assert_eq!(replies[0]["params"]["diagnostics"][0]["code"], "unknown-escape");
+/// ```
+#[derive(Debug)]
+pub struct Server {
+    documents: HashMap<String, Document>,
+    encoding: Encoding,
+    shut_down: bool,
+    exited: bool,
+}
+
+impl Default for Server {
+    fn default() -> Self {
+        Server { documents: HashMap::new(), encoding: Encoding::Utf16, shut_down: false, exited: false }
+    }
+}
+
+impl Server {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Handle one message from the client, and return the messages to send
+    /// back: the response to a request, or notifications.
+    pub fn handle(&mut self, message: &Value) -> Vec<Value> {
+        let params = message.get("params").unwrap_or(&Value::Null);
+        match (message.get("id"), message.get("method").and_then(Value::as_str)) {
+            (Some(id), Some(method)) => {
+                let response = match self.request(method, params) {
+                    Ok(result) => json!({ "jsonrpc": "2.0", "id": id, "result": result }),
+                    Err((code, message)) => json!({ "jsonrpc": "2.0", "id": id, "error": { "code": code, "message": message } }),
+                };
+                vec![response]
+            }
+            (None, Some(method)) => self.notification(method, params),
+            // Responses, which we never ask for, and messages that aren't any
+            _ => Vec::new(),
+        }
+    }
+
+    /// Whether the client sent `exit`.
+    pub fn exited(&self) -> bool {
+        self.exited
+    }
+
+    fn request(&mut self, method: &str, params: &Value) -> Result<Value, (i64, String)> {
+        if self.shut_down {
+            return Err((INVALID_REQUEST, "server is shut down".to_string()));
+        }
+        match method {
+            "initialize" => Ok(self.initialize(params)),
+            "shutdown" => {
+                self.shut_down = true;
+                Ok(Value::Null)
+            }
+            "textDocument/hover" => self.with_document(params, hover),
+            "textDocument/codeAction" => self.with_document(params, code_actions),
+            "textDocument/documentSymbol" => self.with_document(params, document_symbols),
+            _ => Err((METHOD_NOT_FOUND, format!("unsupported method {}", method))),
+        }
+    }
+
+    fn initialize(&mut self, params: &Value) -> Value {
+        let offered = &params["capabilities"]["general"]["positionEncodings"];
+        self.encoding = match offered.as_array() {
+            Some(encodings) if encodings.iter().any(|e| e == "utf-8") => Encoding::Utf8,
+            _ => Encoding::Utf16,
+        };
+        json!({
+            "capabilities": {
+                "positionEncoding": if self.encoding == Encoding::Utf8 { "utf-8" } else { "utf-16" },
+                "textDocumentSync": { "openClose": true, "change": 2 },
+                "hoverProvider": true,
+                "codeActionProvider": { "codeActionKinds": ["quickfix"] },
+                "documentSymbolProvider": true,
+            },
+            "serverInfo": { "name": "nsv-lsp", "version": env!("CARGO_PKG_VERSION") },
+        })
+    }
+
+    fn with_document(&self, params: &Value, f: fn(&Lines, &Document, &str, &Value) -> Option<Value>) -> Result<Value, (i64, String)> {
+        let uri = params["textDocument"]["uri"].as_str().ok_or((INVALID_PARAMS, "missing textDocument.uri".to_string()))?;
+        let document = self.documents.get(uri).ok_or_else(|| (INVALID_PARAMS, format!("{} is not open", uri)))?;
+        f(&document.lines(self.encoding), document, uri, params).ok_or((INVALID_PARAMS, "malformed params".to_string()))
+    }
+
+    fn notification(&mut self, method: &str, params: &Value) -> Vec<Value> {
+        let uri = params["textDocument"]["uri"].as_str();
+        match (method, uri) {
+            ("exit", _) => {
+                self.exited = true;
+                Vec::new()
+            }
+            ("textDocument/didOpen", Some(uri)) => {
+                let Some(text) = params["textDocument"]["text"].as_str() else { return Vec::new() };
+                self.documents.insert(uri.to_string(), Document::new(text, params["textDocument"]["version"].clone()));
+                self.publish_diagnostics(uri)
+            }
+            ("textDocument/didChange", Some(uri)) => {
+                let encoding = self.encoding;
+                let Some(document) = self.documents.get_mut(uri) else { return Vec::new() };
+                for change in params["contentChanges"].as_array().into_iter().flatten() {
+                    let Some(text) = change["text"].as_str() else { continue };
+                    if change.get("range").is_none() {
+                        *document = Document::new(text, Value::Null);
+                        continue;
+                    }
+                    let lines = document.lines(encoding);
+                    if let (Some(start), Some(end)) = (lines.offset(&change["range"]["start"]), lines.offset(&change["range"]["end"])) {
+                        document.edit(start.min(end)..end.max(start), text.as_bytes());
+                    }
+                }
+                document.version = params["textDocument"]["version"].clone();
+                self.publish_diagnostics(uri)
+            }
+            ("textDocument/didClose", Some(uri)) => {
+                self.documents.remove(uri);
+                vec![json!({
+                    "jsonrpc": "2.0",
+                    "method": "textDocument/publishDiagnostics",
+                    "params": { "uri": uri, "diagnostics": [] },
+                })]
+            }
+            _ => Vec::new(),
+        }
+    }
+
+    fn publish_diagnostics(&self, uri: &str) -> Vec<Value> {
+        let document = &self.documents[uri];
+        let lines = document.lines(self.encoding);
+        let diagnostics: Vec<Value> = document.warnings.iter().map(|warning| diagnostic(&lines, warning)).collect();
+        vec![json!({
+            "jsonrpc": "2.0",
+            "method": "textDocument/publishDiagnostics",
+            "params": { "uri": uri, "version": document.version, "diagnostics": diagnostics },
+        })]
+    }
+}
+
+/// Where lines start in a text, to convert between byte offsets and LSP
+/// positions.
+struct Lines<'a> {
+    text: &'a [u8],
+    starts: &'a [usize],
+    encoding: Encoding,
+}
+
+impl Lines<'_> {
+    fn position(&self, offset: usize) -> Value {
+        let line = self.starts.partition_point(|&start| start <= offset) - 1;
+        let character = units(&self.text[self.starts[line]..offset], self.encoding);
+        json!({ "line": line, "character": character })
+    }
+
+    fn range(&self, range: Range<usize>) -> Value {
+        json!({ "start": self.position(range.start), "end": self.position(range.end) })
+    }
+
+    /// The offset of `position`, clamped to the end of its line or of the
+    /// text.
+    fn offset(&self, position: &Value) -> Option<usize> {
+        let line = usize::try_from(position["line"].as_u64()?).ok()?;
+        let character = usize::try_from(position["character"].as_u64()?).ok()?;
+        let Some(&start) = self.starts.get(line) else { return Some(self.text.len()) };
+        let end = self.starts.get(line + 1).map_or(self.text.len(), |&next| next - 1);
+        let line = &self.text[start..end];
+        Some(start + match self.encoding {
+            Encoding::Utf8 => character.min(line.len()),
+            Encoding::Utf16 => utf16_prefix(line, character),
+        })
+    }
+}
+
+/// Where each line of `text` starts.
+fn line_starts(text: &[u8]) -> Vec<usize> {
+    std::iter::once(0).chain(memchr::memchr_iter(b'\n', text).map(|at| at + 1)).collect()
+}
+
+/// Length of `bytes` in position units.
+fn units(bytes: &[u8], encoding: Encoding) -> usize {
+    match encoding {
+        Encoding::Utf8 => bytes.len(),
+        Encoding::Utf16 => bytes.utf8_chunks().map(|chunk| chunk.valid().encode_utf16().count() + chunk.invalid().len()).sum(),
+    }
+}
+
+/// Bytes of `line` in its first `character` UTF-16 units, stopping short of a
+/// character they'd split.
+fn utf16_prefix(line: &[u8], character: usize) -> usize {
+    let (mut bytes, mut units) = (0, 0);
+    for chunk in line.utf8_chunks() {
+        for c in chunk.valid().chars() {
+            if units + c.len_utf16() > character {
+                return bytes;
+            }
+            units += c.len_utf16();
+            bytes += c.len_utf8();
+        }
+        for _ in chunk.invalid() {
+            if units == character {
+                return bytes;
+            }
+            units += 1;
+            bytes += 1;
+        }
+    }
+    bytes
+}
+
+/// The bytes a warning is about.
+fn warning_range(warning: &Warning) -> Range<usize> {
+    match warning.kind {
+        WarningKind::UnknownEscape(_) => warning.pos..warning.pos + 2,
+        WarningKind::DanglingBackslash => warning.pos..warning.pos + 1,
+        WarningKind::NoTerminalLf => warning.pos..warning.pos,
+    }
+}
+
+fn diagnostic(lines: &Lines, warning: &Warning) -> Value {
+    let (code, message) = match warning.kind {
+        WarningKind::UnknownEscape(b) => (
+            "unknown-escape",
+            format!("unknown escape `\\{}`, read as a backslash and the byte after it", b.escape_ascii()),
+        ),
+        WarningKind::DanglingBackslash => ("dangling-backslash", "dangling backslash, dropped when decoding".to_string()),
+        WarningKind::NoTerminalLf => ("no-terminal-lf", "missing LF at the end of the input".to_string()),
+    };
+    json!({
+        "range": lines.range(warning_range(warning)),
+        "severity": 2,
+        "source": "nsv",
+        "code": code,
+        "message": message,
+    })
+}
+
+/// Quick fixes for the warnings in the requested range. Each keeps the
+/// decoded rows as they were.
+fn code_actions(lines: &Lines, document: &Document, uri: &str, params: &Value) -> Option<Value> {
+    let text = lines.text;
+    let requested = lines.offset(&params["range"]["start"])?..lines.offset(&params["range"]["end"])?;
+    let mut actions = Vec::new();
+    for warning in &document.warnings {
+        let range = warning_range(warning);
+        if range.start > requested.end || range.end < requested.start {
+            continue;
+        }
+        let (title, edit, new_text) = match warning.kind {
+            WarningKind::UnknownEscape(b) => (format!("Escape the backslash: `\\\\{}`", b.escape_ascii()), warning.pos..warning.pos, "\\"),
+            // A backslash alone on the last line decodes to an empty cell:
+            // terminating the row, below, keeps it one
+            WarningKind::DanglingBackslash if warning.pos == 0 || text[warning.pos - 1] == b'\n' => continue,
+            WarningKind::DanglingBackslash => ("Remove the dangling backslash".to_string(), range.clone(), ""),
+            WarningKind::NoTerminalLf => ("Terminate the last row".to_string(), range.clone(), "\n\n"),
+        };
+        actions.push(json!({
+            "title": title,
+            "kind": "quickfix",
+            "diagnostics": [diagnostic(lines, warning)],
+            "isPreferred": true,
+            "edit": { "changes": { uri: [{ "range": lines.range(edit), "newText": new_text }] } },
+        }));
+    }
+    Some(Value::Array(actions))
+}
+
+fn hover(lines: &Lines, document: &Document, _: &str, params: &Value) -> Option<Value> {
+    let document = &document.nsv;
+    let text = lines.text;
+    let offset = lines.offset(&params["position"])?;
+    let Some(row) = document.row_at(offset) else { return Some(Value::Null) };
+    let row_start = document.row_range(row)?.start;
+    let line_start = memchr::memrchr(b'\n', &text[row_start..offset]).map_or(row_start, |at| row_start + at + 1);
+    let line_end = memchr::memchr(b'\n', &text[offset..]).map_or(text.len(), |at| offset + at);
+    let column = memchr::memchr_iter(b'\n', &text[row_start..line_start]).count();
+
+    let contents = match document.row(row)?.get(column) {
+        Some(cell) if cell.is_empty() => format!("row {}, column {}: empty cell", row, column),
+        Some(cell) => format!("row {}, column {}\n\n{}", row, column, code_block(cell)),
+        None => format!("end of row {}", row),
+    };
+    Some(json!({
+        "contents": { "kind": "markdown", "value": contents },
+        "range": lines.range(line_start..line_end),
+    }))
+}
+
+/// `value` in a fenced code block longer than any run of backticks in it.
+fn code_block(value: &[u8]) -> String {
+    let value = String::from_utf8_lossy(value);
+    let longest = value.split(|c| c != '`').map(str::len).max().unwrap_or(0);
+    let fence = "`".repeat(longest.max(2) + 1);
+    format!("{}\n{}\n{}", fence, value, fence)
+}
+
+fn document_symbols(lines: &Lines, document: &Document, _: &str, _: &Value) -> Option<Value> {
+    let document = &document.nsv;
+    let text = lines.text;
+    let symbols = (0..document.len())
+        .map(|i| {
+            let range = document.row_range(i).expect("row in range");
+            let first_line = memchr::memchr(b'\n', &text[range.clone()]).map_or(range.end, |at| range.start + at);
+            let detail = match document.row(i).expect("row in range") {
+                [] => "empty row".to_string(),
+                [first, ..] => String::from_utf8_lossy(first).chars().take(60).collect(),
+            };
+            json!({
+                "name": format!("row {}", i),
+                "detail": detail,
+                "kind": 18,
+                "range": lines.range(range.clone()),
+                "selectionRange": lines.range(range.start..first_line),
+            })
+        })
+        .collect();
+    Some(Value::Array(symbols))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Drive `run` with `messages`, framed, and return what it wrote back.
+    fn session(messages: &[Value]) -> (io::Result<bool>, Vec<Value>) {
+        let mut input = Vec::new();
+        for message in messages {
+            write_message(&mut input, message).unwrap();
+        }
+        let mut output = Vec::new();
+        let clean = run(io::Cursor::new(input), &mut output);
+        let mut replies = Vec::new();
+        let mut output = io::Cursor::new(output);
+        while let Some(body) = read_message(&mut output).unwrap() {
+            replies.push(serde_json::from_slice(&body).unwrap());
+        }
+        (clean, replies)
+    }
+
+    fn request(id: u64, method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "id": id, "method": method, "params": params })
+    }
+
+    fn notification(method: &str, params: Value) -> Value {
+        json!({ "jsonrpc": "2.0", "method": method, "params": params })
+    }
+
+    fn open(text: &str) -> Value {
+        notification("textDocument/didOpen", json!({ "textDocument": { "uri": "file:///t.nsv", "languageId": "nsv", "version": 1, "text": text } }))
+    }
+
+    fn at(line: u64, character: u64) -> Value {
+        json!({ "line": line, "character": character })
+    }
+
+    fn doc() -> Value {
+        json!({ "uri": "file:///t.nsv" })
+    }
+
+    #[test]
+    fn test_session() {
+        let initialize = request(1, "initialize", json!({ "capabilities": {} }));
+        let (clean, replies) = session(&[
+            initialize,
+            notification("initialized", json!({})),
+            // Row 0: `a\x`, `é`; row 1: `b\` (dangling), unterminated
+            open("a\\x\n\u{e9}\n\nb\\\n"),
+            request(2, "textDocument/hover", json!({ "textDocument": doc(), "position": at(1, 1) })),
+            request(3, "textDocument/hover", json!({ "textDocument": doc(), "position": at(2, 0) })),
+            request(4, "textDocument/documentSymbol", json!({ "textDocument": doc() })),
+            request(5, "textDocument/codeAction", json!({ "textDocument": doc(), "range": { "start": at(0, 1), "end": at(0, 1) }, "context": { "diagnostics": [] } })),
+            // Replace `x` with `n`, then append the row terminator
+            notification("textDocument/didChange", json!({
+                "textDocument": { "uri": "file:///t.nsv", "version": 2 },
+                "contentChanges": [
+                    { "range": { "start": at(0, 2), "end": at(0, 3) }, "text": "n" },
+                    { "range": { "start": at(4, 0), "end": at(4, 0) }, "text": "\n" },
+                ],
+            })),
+            request(6, "textDocument/hover", json!({ "textDocument": doc(), "position": at(0, 0) })),
+            request(7, "shutdown", Value::Null),
+            notification("exit", Value::Null),
+        ]);
+        assert!(clean.unwrap());
+        assert_eq!(replies.len(), 9);
+
+        assert_eq!(replies[0]["id"], 1);
+        assert_eq!(replies[0]["result"]["capabilities"]["positionEncoding"], "utf-16");
+        assert_eq!(replies[0]["result"]["capabilities"]["textDocumentSync"]["change"], 2);
+
+        let published = &replies[1]["params"];
+        assert_eq!(published["version"], 1);
+        let diagnostics = published["diagnostics"].as_array().unwrap();
+        let codes: Vec<&Value> = diagnostics.iter().map(|d| &d["code"]).collect();
+        assert_eq!(codes, ["unknown-escape", "dangling-backslash"]);
+        assert_eq!(diagnostics[0]["range"], json!({ "start": at(0, 1), "end": at(0, 3) }));
+        assert_eq!(diagnostics[1]["range"], json!({ "start": at(3, 1), "end": at(3, 2) }));
+
+        assert_eq!(replies[2]["result"]["contents"]["value"], "row 0, column 1\n\n```\n\u{e9}\n```");
+        assert_eq!(replies[2]["result"]["range"], json!({ "start": at(1, 0), "end": at(1, 1) }));
+        assert_eq!(replies[3]["result"]["contents"]["value"], "end of row 0");
+
+        let symbols = replies[4]["result"].as_array().unwrap();
+        assert_eq!(symbols.len(), 2);
+        assert_eq!((&symbols[0]["name"], &symbols[0]["detail"]), (&json!("row 0"), &json!("a\\x")));
+        assert_eq!(symbols[1]["range"], json!({ "start": at(3, 0), "end": at(4, 0) }));
+        assert_eq!(symbols[1]["selectionRange"], json!({ "start": at(3, 0), "end": at(3, 2) }));
+
+        let actions = replies[5]["result"].as_array().unwrap();
+        assert_eq!(actions.len(), 1);
+        assert_eq!(actions[0]["edit"]["changes"]["file:///t.nsv"][0], json!({ "range": { "start": at(0, 1), "end": at(0, 1) }, "newText": "\\" }));
+
+        // After the edits: `a\n` is an escaped LF, and the last row is terminated
+        let published = &replies[6]["params"];
+        assert_eq!(published["version"], 2);
+        assert_eq!(published["diagnostics"].as_array().unwrap().len(), 1);
+        assert_eq!(replies[7]["result"]["contents"]["value"], "row 0, column 0\n\n```\na\n\n```");
+        assert_eq!(replies[8], json!({ "jsonrpc": "2.0", "id": 7, "result": null }));
+    }
+
+    #[test]
+    fn test_quick_fixes_keep_rows() {
+        for text in ["a\\q\n\\x\\\n\n", "x\\", "\\", "a\n\n\\", "b\\\nc\\\\d\\", "\u{e9}\\\u{e9}\n"] {
+            let mut server = Server::new();
+            server.handle(&request(1, "initialize", json!({ "capabilities": {} })));
+            server.handle(&open(text));
+            let starts = line_starts(text.as_bytes());
+            let lines = Lines { text: text.as_bytes(), starts: &starts, encoding: Encoding::Utf16 };
+            let end = lines.position(text.len());
+            let replies = server.handle(&request(2, "textDocument/codeAction", json!({ "textDocument": doc(), "range": { "start": at(0, 0), "end": end } })));
+
+            // Fixes don't overlap; apply them from the end
+            let mut edits: Vec<Value> = replies[0]["result"].as_array().unwrap().iter().map(|action| action["edit"]["changes"]["file:///t.nsv"][0].clone()).collect();
+            edits.sort_by_key(|edit| std::cmp::Reverse(lines.offset(&edit["range"]["start"])));
+            let mut fixed = text.as_bytes().to_vec();
+            for edit in edits {
+                let range = lines.offset(&edit["range"]["start"]).unwrap()..lines.offset(&edit["range"]["end"]).unwrap();
+                fixed.splice(range, edit["newText"].as_str().unwrap().bytes());
+            }
+            assert_eq!(check(&fixed), [], "{:?} fixed as {:?}", text, String::from_utf8_lossy(&fixed));
+            assert_eq!(crate::decode_bytes(&fixed), crate::decode_bytes(text.as_bytes()), "{:?}", text);
+        }
+    }
+
+    #[test]
+    fn test_edits_keep_warnings() {
+        let replacements = ["", "\n", "\\", "\\q", "x\n\n", "\\\n", "y"];
+        for seed in 0..200 {
+            let text = crate::test_util::random_input(seed, (seed as usize * 7) % 80);
+            let mut document = Document::new(std::str::from_utf8(&text).unwrap(), Value::Null);
+            let mut state = seed as usize;
+            for step in 0..20 {
+                state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+                let len = document.nsv.text().len();
+                let start = (state >> 33) % (len + 1);
+                let end = start + (state >> 17) % (len - start + 1).min(4);
+                let replacement = replacements[(state >> 7) % replacements.len()];
+                document.edit(start..end, replacement.as_bytes());
+
+                let text = document.nsv.text();
+                assert_eq!(document.warnings, check(text), "seed {} step {}: {:?}", seed, step, String::from_utf8_lossy(text));
+                assert_eq!(document.line_starts, line_starts(text), "seed {} step {}", seed, step);
+            }
+        }
+    }
+
+    #[test]
+    fn test_positions() {
+        // 2 bytes, 1 unit; 4 bytes, 2 units; an invalid byte counts as one
+        let text = "\u{e9}\u{1F600}x\n\ny".as_bytes();
+        let text = [&text[..7], &[0xff], &text[7..]].concat();
+        let starts = line_starts(&text);
+        let utf16 = Lines { text: &text, starts: &starts, encoding: Encoding::Utf16 };
+        assert_eq!([0, 2, 6, 7, 8, 9, 10, 11].map(|offset| utf16.position(offset)), [at(0, 0), at(0, 1), at(0, 3), at(0, 4), at(0, 5), at(1, 0), at(2, 0), at(2, 1)]);
+        assert_eq!([at(0, 1), at(0, 2), at(0, 3), at(0, 5), at(0, 99), at(2, 1), at(7, 0)].map(|p| utf16.offset(&p)), [2, 2, 6, 8, 8, 11, 11].map(Some));
+
+        let utf8 = Lines { encoding: Encoding::Utf8, ..utf16 };
+        assert_eq!(utf8.position(6), at(0, 6));
+        assert_eq!(utf8.offset(&at(0, 99)), Some(8));
+
+        let mut server = Server::new();
+        let replies = server.handle(&request(1, "initialize", json!({ "capabilities": { "general": { "positionEncodings": ["utf-16", "utf-8"] } } })));
+        assert_eq!(replies[0]["result"]["capabilities"]["positionEncoding"], "utf-8");
+    }
+
+    #[test]
+    fn test_protocol_errors() {
+        let (clean, replies) = session(&[
+            request(1, "textDocument/rename", json!({})),
+            request(2, "textDocument/hover", json!({ "textDocument": doc(), "position": at(0, 0) })),
+            request(3, "shutdown", Value::Null),
+            request(4, "shutdown", Value::Null),
+        ]);
+        // The input ended without `exit`
+        assert!(!clean.unwrap());
+        let codes: Vec<&Value> = replies.iter().map(|reply| &reply["error"]["code"]).collect();
+        assert_eq!(codes, [&json!(METHOD_NOT_FOUND), &json!(INVALID_PARAMS), &Value::Null, &json!(INVALID_REQUEST)]);
+
+        // Exiting without shutting down
+        let (clean, _) = session(&[notification("exit", Value::Null)]);
+        assert!(!clean.unwrap());
+
+        let mut output = Vec::new();
+        let clean = run(io::Cursor::new(b"Content-Length: 3\r\n\r\n{x}".to_vec()), &mut output).unwrap();
+        assert!(!clean);
+        let reply: Value = serde_json::from_slice(&read_message(&mut io::Cursor::new(output)).unwrap().unwrap()).unwrap();
+        assert_eq!(reply["error"]["code"], PARSE_ERROR);
+
+        assert!(run(io::Cursor::new(b"Content-Type: x\r\n\r\n".to_vec()), io::sink()).is_err());
+
+        // A length past the input is an error, not an allocation of that size
+        let huge = format!("Content-Length: {}\r\n\r\n{{}}", u64::MAX);
+        let err = read_message(&mut io::Cursor::new(huge.into_bytes())).unwrap_err();
+        assert_eq!(err.kind(), io::ErrorKind::UnexpectedEof);
+    }
+}
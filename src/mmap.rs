@@ -0,0 +1,131 @@
+//! Memory-mapped decoding (feature `mmap`).
+//!
+//! Decoded cells borrow straight from the mapped pages, so a file is never
+//! copied into a heap buffer first, and the parallel decoder chunks the mapping
+//! like any other slice.
+
+use std::borrow::Cow;
+use std::fs::File;
+use std::io;
+use std::path::Path;
+
+use memmap2::Mmap;
+
+use crate::{Cancelled, Columns, DecodeOptions, Rows};
+
+/// A read-only memory mapping of an NSV file.
+///
+/// ```no_run
+/// use nsv::mmap::MappedFile;
+///
+/// // SAFETY: nothing else modifies or truncates the file while it is mapped.
+/// let file = unsafe { MappedFile::open("data.nsv") }?;
+/// for row in file.rows() {
+///     println!("{} cells", row.len());
+/// }
+/// # Ok::<(), std::io::Error>(())
+/// ```
+#[derive(Debug)]
+pub struct MappedFile {
+    map: Mmap,
+}
+
+impl MappedFile {
+    /// Map the file at `path`.
+    ///
+    /// # Safety
+    ///
+    /// The file must not be modified or truncated while it is mapped: the
+    /// decoded cells are views of its pages, so outside writes change them
+    /// under the borrow, and truncation makes reads fault.
+    pub unsafe fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let file = File::open(path)?;
+        let map = Mmap::map(&file)?;
+        Ok(MappedFile { map })
+    }
+
+    /// The mapped bytes.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.map
+    }
+
+    /// Decode the whole file. See [`crate::decode_bytes`].
+    pub fn decode(&self) -> Vec<Vec<Cow<'_, [u8]>>> {
+        crate::decode_bytes(self.as_bytes())
+    }
+
+    /// Decode the whole file with explicit options. See [`crate::decode_bytes_with`].
+    pub fn decode_with(&self, options: &DecodeOptions) -> Result<Vec<Vec<Cow<'_, [u8]>>>, Cancelled> {
+        crate::decode_bytes_with(self.as_bytes(), options)
+    }
+
+    /// Decode only `columns`. See [`crate::decode_bytes_projected`].
+    pub fn decode_projected(&self, columns: impl Columns) -> Vec<Vec<Cow<'_, [u8]>>> {
+        crate::decode_bytes_projected(self.as_bytes(), columns)
+    }
+
+    /// Iterate over the rows lazily. See [`crate::rows`].
+    pub fn rows(&self) -> Rows<'_> {
+        crate::rows(self.as_bytes())
+    }
+}
+
+impl AsRef<[u8]> for MappedFile {
+    fn as_ref(&self) -> &[u8] {
+        self.as_bytes()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Run `f` on a mapping of a temporary file holding `contents`.
+    fn with_mapped(name: &str, contents: &[u8], f: impl FnOnce(&MappedFile)) {
+        let path = std::env::temp_dir().join(format!("nsv-mmap-{}-{}.nsv", std::process::id(), name));
+        std::fs::write(&path, contents).unwrap();
+        // SAFETY: the file is private to this test and never written again.
+        let file = unsafe { MappedFile::open(&path) }.unwrap();
+        f(&file);
+        // Windows refuses to delete a file that is still mapped
+        drop(file);
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_mapped_decode() {
+        let data: Vec<Vec<String>> = (0..50_000)
+            .map(|i| vec![format!("r{}", i), format!("a\nb\\{}", i), String::new()])
+            .collect();
+        let encoded = crate::encode(&data);
+        with_mapped("decode", encoded.as_bytes(), |file| {
+            assert_eq!(file.as_bytes(), encoded.as_bytes());
+            let expected = crate::decode_bytes(encoded.as_bytes());
+            assert_eq!(file.decode(), expected);
+            assert_eq!(file.decode_with(&DecodeOptions::new().sequential(true)).unwrap(), expected);
+            assert_eq!(file.decode_projected(&[1]), crate::decode_bytes_projected(encoded.as_bytes(), &[1]));
+            let rows: Vec<Vec<_>> = file.rows().map(|row| row.iter().collect()).collect();
+            assert_eq!(rows, expected);
+
+            // Cells without escapes point into the mapping
+            let range = file.as_bytes().as_ptr_range();
+            assert!(matches!(&file.decode()[7][0], Cow::Borrowed(cell) if range.contains(&cell.as_ptr())));
+        });
+    }
+
+    #[test]
+    fn test_mapped_rows_edge_cases() {
+        for (name, input) in [
+            ("empty", &b""[..]),
+            ("empty-rows", b"\n\n\n"),
+            ("leading-empty", b"\na\n\n"),
+            ("unterminated", b"a\n\nb\nc"),
+            ("open-row", b"a\nb\n"),
+        ] {
+            with_mapped(name, input, |file| {
+                let rows: Vec<Vec<_>> = file.rows().map(|row| row.iter().collect()).collect();
+                assert_eq!(rows, crate::decode_bytes(input), "{}", name);
+            });
+        }
+    }
+}
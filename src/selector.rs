@@ -0,0 +1,344 @@
+//! Column selection beyond explicit index lists.
+
+use std::borrow::Cow;
+use std::ops::{Bound, RangeBounds};
+
+/// Which columns of each row to keep, and in what order.
+///
+/// Built from picks, applied in order, minus exclusions. Negative positions
+/// count from the end of each row: `-1` is its last cell.
+///
+/// - [`index`](Self::index) always yields one cell, empty when the row has no
+///   such column.
+/// - [`range`](Self::range) with both ends counted from the start (`2..5`) is
+///   shorthand for those indices. Open ranges (`10..`) and ranges counted from
+///   the end (`-3..`) yield only the cells the row has.
+/// - [`exclude`](Self::exclude) and [`exclude_range`](Self::exclude_range)
+///   drop columns from every pick.
+///
+/// ```
+/// use nsv::ColumnSelector;
+///
+/// let all_but = ColumnSelector::all().exclude(3).exclude(7);
+/// let tail = ColumnSelector::new().range(10..);
+/// let last = ColumnSelector::new().index(-1);
+/// let listed = ColumnSelector::from(&[2, 0][..]);
+/// ```
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ColumnSelector {
+    picks: Vec<Span>,
+    excluded: Vec<Span>,
+}
+
+/// Positions `start..end`; negative ones count from the end of the row.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Span {
+    start: isize,
+    end: Option<isize>,
+    /// A single index: yields a cell even past the end of the row.
+    single: bool,
+}
+
+impl Span {
+    fn new(range: impl RangeBounds<isize>) -> Self {
+        // No row reaches `isize::MAX` cells: past it is as good as unbounded.
+        let start = match range.start_bound() {
+            Bound::Included(&s) => s,
+            Bound::Excluded(&s) => s.saturating_add(1),
+            Bound::Unbounded => 0,
+        };
+        let end = match range.end_bound() {
+            Bound::Included(&e) => e.checked_add(1),
+            Bound::Excluded(&e) => Some(e),
+            Bound::Unbounded => None,
+        };
+        // `-1..=-1` ends at 0, "the end of the row".
+        let end = match end {
+            Some(0) if start < 0 => None,
+            end => end,
+        };
+        Span { start, end, single: false }
+    }
+
+    /// Whether the columns covered don't depend on the row's width.
+    fn is_static(&self) -> bool {
+        self.start >= 0 && (self.single || self.end.is_some_and(|e| e >= 0))
+    }
+
+    /// Whether `col` is covered in a row of `width` cells.
+    fn contains(&self, col: usize, width: usize) -> bool {
+        let at = |pos| absolute(pos, width);
+        at(self.start) <= col && self.end.is_none_or(|end| col < at(end))
+    }
+
+    /// Absolute `start..end` in a row of `width` cells, clamped to `0..width`
+    /// unless static.
+    fn resolve(&self, width: usize) -> (usize, usize) {
+        let start = absolute(self.start, width);
+        let end = self.end.map_or(width, |end| absolute(end, width));
+        if self.is_static() {
+            (start, end)
+        } else {
+            (start.min(width), end.min(width))
+        }
+    }
+
+    /// Whether `col` can be covered in a row of some width.
+    fn may_contain(&self, col: usize) -> bool {
+        if self.start < 0 || self.end.is_some_and(|e| e < 0) {
+            return true;
+        }
+        self.start as usize <= col && self.end.is_none_or(|e| col < e as usize)
+    }
+}
+
+/// Position `pos` in a row of `width` cells; before the start clamps to 0.
+fn absolute(pos: isize, width: usize) -> usize {
+    if pos < 0 {
+        width.saturating_sub(pos.unsigned_abs())
+    } else {
+        pos as usize
+    }
+}
+
+impl ColumnSelector {
+    /// Selects nothing; add picks with [`index`](Self::index) and [`range`](Self::range).
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Every column.
+    pub fn all() -> Self {
+        Self::new().range(..)
+    }
+
+    /// Pick column `i`; negative counts from the end.
+    pub fn index(mut self, i: isize) -> Self {
+        self.picks.push(Span { single: true, ..Span::new(i..=i) });
+        self
+    }
+
+    /// Pick the columns in `range`; negative ends count from the end.
+    pub fn range(mut self, range: impl RangeBounds<isize>) -> Self {
+        self.picks.push(Span::new(range));
+        self
+    }
+
+    /// Drop column `i` from every pick; negative counts from the end.
+    pub fn exclude(self, i: isize) -> Self {
+        self.exclude_range(i..=i)
+    }
+
+    /// Drop the columns in `range` from every pick.
+    pub fn exclude_range(mut self, range: impl RangeBounds<isize>) -> Self {
+        self.excluded.push(Span::new(range));
+        self
+    }
+
+    /// The selected columns when they don't depend on each row's width.
+    pub(crate) fn static_columns(&self) -> Option<Vec<usize>> {
+        if !self.picks.iter().all(Span::is_static) || self.excluded.iter().any(|x| x.start < 0 || x.end.is_some_and(|e| e < 0)) {
+            return None;
+        }
+        let mut columns = Vec::new();
+        for pick in &self.picks {
+            let (start, end) = pick.resolve(0);
+            let end = if pick.single { start + 1 } else { end };
+            columns.extend((start..end).filter(|&col| !self.excluded.iter().any(|x| x.may_contain(col))));
+        }
+        Some(columns)
+    }
+
+    /// Call `f` with each selected column of a row of `width` cells, in order:
+    /// `None` for an index past the end of the row.
+    pub(crate) fn for_each(&self, width: usize, mut f: impl FnMut(Option<usize>)) {
+        let excluded = |col: usize| self.excluded.iter().any(|x| x.contains(col, width));
+        for pick in &self.picks {
+            let (start, end) = pick.resolve(width);
+            if pick.single {
+                // A negative index before the start of the row is missing too.
+                let missing = pick.start < 0 && pick.start.unsigned_abs() > width;
+                if missing {
+                    f(None);
+                } else if !excluded(start) {
+                    f((start < width).then_some(start));
+                }
+                continue;
+            }
+            for col in start..end {
+                if !excluded(col) {
+                    f((col < width).then_some(col));
+                }
+            }
+        }
+    }
+
+    /// Whether column `col` can be selected in a row of some width; cells
+    /// that can't are skipped outright.
+    pub(crate) fn may_select(&self, col: usize) -> bool {
+        // Only exclusions counted from the start rule a column out for every width.
+        let excluded = self.excluded.iter().any(|x| x.start >= 0 && x.end.is_none_or(|e| e >= 0) && x.may_contain(col));
+        !excluded && self.picks.iter().any(|pick| pick.may_contain(col))
+    }
+}
+
+impl From<&[usize]> for ColumnSelector {
+    fn from(columns: &[usize]) -> Self {
+        // Columns past `isize::MAX` are missing from every row, as it is
+        columns.iter().fold(Self::new(), |selector, &col| selector.index(isize::try_from(col).unwrap_or(isize::MAX)))
+    }
+}
+
+/// The columns a projected decoder keeps: a list of indices, or a
+/// [`ColumnSelector`].
+///
+/// ```
+/// use nsv::{decode_bytes_projected, ColumnSelector};
+///
+/// let input = b"a\nb\nc\n\nd\n\n";
+/// assert_eq!(decode_bytes_projected(input, &[1, 0]), [[&b"b"[..], b"a"], [b"", b"d"]]);
+/// assert_eq!(decode_bytes_projected(input, &ColumnSelector::new().index(-1)), [[&b"c"[..]], [b"d"]]);
+/// ```
+pub trait Columns {
+    /// The columns, when they don't depend on each row's width.
+    fn static_columns(&self) -> Option<Cow<'_, [usize]>>;
+
+    /// A selector for the same columns.
+    fn to_selector(&self) -> Cow<'_, ColumnSelector>;
+}
+
+impl Columns for &[usize] {
+    fn static_columns(&self) -> Option<Cow<'_, [usize]>> {
+        Some(Cow::Borrowed(self))
+    }
+
+    fn to_selector(&self) -> Cow<'_, ColumnSelector> {
+        Cow::Owned(ColumnSelector::from(*self))
+    }
+}
+
+impl<const N: usize> Columns for &[usize; N] {
+    fn static_columns(&self) -> Option<Cow<'_, [usize]>> {
+        Some(Cow::Borrowed(&self[..]))
+    }
+
+    fn to_selector(&self) -> Cow<'_, ColumnSelector> {
+        Cow::Owned(ColumnSelector::from(&self[..]))
+    }
+}
+
+impl Columns for &Vec<usize> {
+    fn static_columns(&self) -> Option<Cow<'_, [usize]>> {
+        Some(Cow::Borrowed(self))
+    }
+
+    fn to_selector(&self) -> Cow<'_, ColumnSelector> {
+        Cow::Owned(ColumnSelector::from(&self[..]))
+    }
+}
+
+impl Columns for &ColumnSelector {
+    fn static_columns(&self) -> Option<Cow<'_, [usize]>> {
+        ColumnSelector::static_columns(self).map(Cow::Owned)
+    }
+
+    fn to_selector(&self) -> Cow<'_, ColumnSelector> {
+        Cow::Borrowed(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn select(selector: &ColumnSelector, width: usize) -> Vec<Option<usize>> {
+        let mut out = Vec::new();
+        selector.for_each(width, |col| out.push(col));
+        out
+    }
+
+    #[test]
+    fn test_picks() {
+        let s = ColumnSelector::new().index(2).index(0).index(5);
+        assert_eq!(select(&s, 4), [Some(2), Some(0), None]);
+        assert_eq!(s.static_columns(), Some(vec![2, 0, 5]));
+
+        let s = ColumnSelector::new().range(1..3);
+        assert_eq!(select(&s, 2), [Some(1), None]);
+        assert_eq!(s.static_columns(), Some(vec![1, 2]));
+
+        let s = ColumnSelector::new().range(2..);
+        assert_eq!(select(&s, 5), [Some(2), Some(3), Some(4)]);
+        assert_eq!(select(&s, 1), []);
+        assert_eq!(s.static_columns(), None);
+    }
+
+    #[test]
+    fn test_from_end() {
+        let last = ColumnSelector::new().index(-1);
+        assert_eq!(select(&last, 3), [Some(2)]);
+        assert_eq!(select(&last, 0), [None]);
+
+        let s = ColumnSelector::new().index(-3);
+        assert_eq!(select(&s, 5), [Some(2)]);
+        assert_eq!(select(&s, 2), [None]);
+
+        let tail = ColumnSelector::new().range(-2..);
+        assert_eq!(select(&tail, 5), [Some(3), Some(4)]);
+        assert_eq!(select(&tail, 1), [Some(0)]);
+
+        // Spelled out: clippy flags `1..-1` as an empty range
+        let s = ColumnSelector::new().range((Bound::Included(1), Bound::Excluded(-1)));
+        assert_eq!(select(&s, 4), [Some(1), Some(2)]);
+        assert_eq!(select(&s, 1), []);
+
+        assert_eq!(select(&ColumnSelector::new().range(-2..=-1), 3), [Some(1), Some(2)]);
+    }
+
+    #[test]
+    fn test_exclusions() {
+        let s = ColumnSelector::all().exclude(1).exclude(3);
+        assert_eq!(select(&s, 5), [Some(0), Some(2), Some(4)]);
+        assert!(!s.may_select(1) && s.may_select(2) && s.may_select(1000));
+
+        let s = ColumnSelector::all().exclude(-1);
+        assert_eq!(select(&s, 3), [Some(0), Some(1)]);
+        assert!(s.may_select(7));
+
+        let s = ColumnSelector::new().range(0..6).exclude_range(2..);
+        assert_eq!(s.static_columns(), Some(vec![0, 1]));
+        assert!(!s.may_select(2) && !s.may_select(9));
+
+        let s = ColumnSelector::new().index(2).index(6).exclude(2).exclude(6);
+        assert_eq!(select(&s, 5), []);
+        assert_eq!(s.static_columns(), Some(vec![]));
+    }
+
+    #[test]
+    fn test_from_list() {
+        let s = ColumnSelector::from(&[3, 1][..]);
+        assert_eq!(s.static_columns(), Some(vec![3, 1]));
+        assert!(s.may_select(3) && !s.may_select(2));
+
+        let s = ColumnSelector::from(&[usize::MAX][..]);
+        assert_eq!(select(&s, 3), [None]);
+    }
+
+    #[test]
+    fn test_extreme_positions() {
+        let max = isize::MAX;
+        let s = ColumnSelector::new().index(max);
+        assert_eq!(select(&s, 3), [None]);
+        assert_eq!(s.static_columns(), Some(vec![max as usize]));
+        assert!(s.may_select(max as usize) && !s.may_select(0));
+
+        let s = ColumnSelector::new().range(1..=max).exclude(max);
+        assert_eq!(select(&s, 3), [Some(1), Some(2)]);
+        assert!(s.may_select(5) && !s.may_select(max as usize));
+        assert_eq!(select(&ColumnSelector::all().exclude_range(max..=max), 2), [Some(0), Some(1)]);
+        assert_eq!(select(&ColumnSelector::new().range((Bound::Excluded(max), Bound::Unbounded)), 2), []);
+
+        let s = ColumnSelector::new().index(isize::MIN).range(isize::MIN..=-2);
+        assert_eq!(select(&s, 3), [None, Some(0), Some(1)]);
+    }
+}
@@ -0,0 +1,95 @@
+//! Refcounted cells over a shared input (feature `bytes`).
+//!
+//! Cells of [`decode_shared`] are [`Bytes`] handles: views into the input
+//! buffer when they need no unescaping, fresh buffers otherwise. They carry
+//! no lifetime, so rows can outlive the code that received the input or move
+//! across tasks, while the input is freed once the last view of it is dropped.
+
+use std::borrow::Cow;
+
+use bytes::Bytes;
+
+use crate::{AllColumns, Cancelled, DecodeOptions};
+
+/// Decode `input` into refcounted cells. Same rows and cells as
+/// [`crate::decode_bytes`].
+///
+/// Cells without escapes are [`Bytes::slice`] views of `input`, with no copy.
+/// [`Writer::write_row`](crate::Writer::write_row) takes `Bytes` cells as they
+/// are.
+///
+/// ```
+/// use bytes::Bytes;
+///
+/// let input = Bytes::from_static(b"a\nb\\nc\n\n");
+/// let rows = nsv::decode_shared(input.clone());
+/// assert_eq!(rows, [[&b"a"[..], b"b\nc"]]);
+/// assert_eq!(rows[0][0].as_ptr(), input.as_ptr());
+/// ```
+pub fn decode_shared(input: Bytes) -> Vec<Vec<Bytes>> {
+    decode_shared_with(input, &DecodeOptions::default()).expect("no cancel token")
+}
+
+/// [`decode_shared`] with explicit [`DecodeOptions`].
+///
+/// Fails only when the options' [`CancelToken`](crate::CancelToken) is
+/// cancelled mid-decode.
+pub fn decode_shared_with(input: Bytes, options: &DecodeOptions) -> Result<Vec<Vec<Bytes>>, Cancelled> {
+    if input.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let rows = options.decode_rows(&AllColumns, &input)?;
+    Ok(options.map_rows(input.len(), rows, |row| row.into_iter().map(|cell| shared(&input, cell)).collect()))
+}
+
+fn shared(input: &Bytes, cell: Cow<'_, [u8]>) -> Bytes {
+    match cell {
+        Cow::Borrowed([]) => Bytes::new(),
+        Cow::Borrowed(cell) => input.slice_ref(cell),
+        Cow::Owned(cell) => Bytes::from(cell),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode_shared() {
+        for input in [&b""[..], b"\n\n", b"a\n\\\nb\\\\\n\nc\\nd\n", b"x\\q\n\n\\"] {
+            let expected: Vec<Vec<Vec<u8>>> = crate::decode_bytes(input).into_iter().map(|row| row.into_iter().map(Cow::into_owned).collect()).collect();
+            let rows: Vec<Vec<Vec<u8>>> = decode_shared(Bytes::copy_from_slice(input)).into_iter().map(|row| row.into_iter().map(Vec::from).collect()).collect();
+            assert_eq!(rows, expected, "{:?}", String::from_utf8_lossy(input));
+        }
+    }
+
+    #[test]
+    fn test_decode_shared_views() {
+        let data: Vec<Vec<String>> = (0..40_000).map(|i| vec![format!("r{}", i), format!("a\nb{}", i), String::new()]).collect();
+        let input = Bytes::from(crate::encode(&data).into_bytes());
+        assert!(input.len() > crate::PARALLEL_THRESHOLD);
+
+        let sequential = DecodeOptions::new().sequential(true);
+        for rows in [decode_shared(input.clone()), decode_shared_with(input.clone(), &sequential).unwrap()] {
+            assert_eq!(rows, data);
+            // Plain cells point into the input; escaped ones don't
+            let range = input.as_ptr_range();
+            assert!(range.contains(&rows[9][0].as_ptr()));
+            assert!(!range.contains(&rows[9][1].as_ptr()));
+        }
+    }
+
+    #[test]
+    fn test_cells_outlive_input_handle() {
+        let rows = {
+            let input = Bytes::from(b"kept\nx\\\\\n\n".to_vec());
+            decode_shared(input)
+        };
+        assert_eq!(rows, [[&b"kept"[..], b"x\\"]]);
+
+        let mut w = crate::Writer::new(Vec::new());
+        w.write_row(&rows[0]).unwrap();
+        assert_eq!(w.into_inner(), b"kept\nx\\\\\n\n");
+    }
+}
@@ -0,0 +1,781 @@
+//! Decoded tables that own their cells in a single buffer.
+//!
+//! [`crate::decode_bytes`] borrows from its input and gives each unescaped
+//! cell its own allocation. A [`Table`] instead copies every cell, unescaped,
+//! into one arena, and finds cells through two offset arrays: three
+//! allocations in all, however many cells, and no borrow of the input.
+//!
+//! A table can also promote its first row to a header, look columns up by
+//! name, and be edited. Edits splice the arena in place: inserting or removing
+//! a row costs a copy of the rows after it, while column edits and sorting
+//! rebuild the whole arena.
+
+use std::cmp::Ordering;
+use std::fmt;
+use std::iter::FusedIterator;
+use std::ops::Range;
+
+use crate::{Cancelled, DecodeOptions};
+
+/// Rows of byte cells stored contiguously, with an optional header.
+///
+/// Cell `i` is `data[cells[i]..cells[i + 1]]`, and row `r` is made of cells
+/// `rows[r]..rows[r + 1]`. The header, when there is one, is kept apart from
+/// the rows: it isn't counted by [`len`](Self::len) nor indexed by
+/// [`row`](Self::row).
+///
+/// Rows may be ragged. Cells past the end of a row read as `None`, and edits
+/// that need them pad the row with empty cells.
+///
+/// ```
+/// use nsv::Table;
+///
+/// let table = Table::decode(b"name\nage\n\nAlice\n30\n\nBob\\n\n\n").with_header();
+/// assert_eq!(table.len(), 2);
+/// assert_eq!(table.get(0, "name"), Some(&b"Alice"[..]));
+/// assert_eq!(table.get(1, 0), Some(&b"Bob\n"[..]));
+/// assert_eq!(table.get(1, "age"), None);
+///
+/// for row in &table {
+///     println!("{} cells", row.len());
+/// }
+/// assert_eq!(table.encode(), b"name\nage\n\nAlice\n30\n\nBob\\n\n\n");
+/// ```
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct Table {
+    data: Vec<u8>,
+    cells: Vec<usize>,
+    rows: Vec<usize>,
+    header: Option<Vec<Vec<u8>>>,
+}
+
+impl Default for Table {
+    fn default() -> Self {
+        Table { data: Vec::new(), cells: vec![0], rows: vec![0], header: None }
+    }
+}
+
+impl fmt::Debug for Table {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let rows = Rows(self);
+        match &self.header {
+            None => rows.fmt(f),
+            Some(header) => {
+                let header: Vec<_> = header.iter().map(|name| String::from_utf8_lossy(name)).collect();
+                f.debug_struct("Table").field("header", &header).field("rows", &rows).finish()
+            }
+        }
+    }
+}
+
+/// Debug helper: the rows of a table, without its header.
+struct Rows<'t>(&'t Table);
+
+impl fmt::Debug for Rows<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.0.iter()).finish()
+    }
+}
+
+impl Table {
+    /// Decode `input` into a table. Same rows and cells as
+    /// [`crate::decode_bytes`], and parallel above the same threshold.
+    pub fn decode(input: &[u8]) -> Self {
+        Self::decode_with(input, &DecodeOptions::default()).expect("no cancel token")
+    }
+
+    /// [`Table::decode`] with explicit [`DecodeOptions`].
+    ///
+    /// In parallel, each chunk of rows is decoded into its own table, and the
+    /// tables are then concatenated. Fails only when the options'
+    /// [`CancelToken`](crate::CancelToken) is cancelled mid-decode.
+    pub fn decode_with(input: &[u8], options: &DecodeOptions) -> Result<Self, Cancelled> {
+        let pieces = options.decode_pieces(input, decode_piece, Table::len)?;
+        Ok(concat(pieces))
+    }
+
+    /// Number of rows.
+    pub fn len(&self) -> usize {
+        self.rows.len() - 1
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Number of cells, over all rows.
+    pub fn cell_count(&self) -> usize {
+        self.cells.len() - 1
+    }
+
+    /// Row `row`, or `None` past the last row.
+    pub fn row(&self, row: usize) -> Option<TableRow<'_>> {
+        (row < self.len()).then(|| self.row_unchecked(row))
+    }
+
+    /// Cell `col` of row `row`, or `None` if there is no such cell. `col` is
+    /// an index or a header name; see [`ColumnKey`].
+    pub fn get(&self, row: usize, col: impl ColumnKey) -> Option<&[u8]> {
+        self.row(row)?.get(col.index_in(self)?)
+    }
+
+    pub fn iter(&self) -> TableRows<'_> {
+        TableRows { table: self, front: 0, back: self.len() }
+    }
+
+    /// Column `col` across all rows, `None` in rows too short to have it; or
+    /// `None` if `col` is a name missing from the header.
+    ///
+    /// ```
+    /// let table = nsv::Table::decode(b"id\ncountry\n\n1\nFR\n\n2\n\n").with_header();
+    /// let countries: Vec<_> = table.column("country").unwrap().collect();
+    /// assert_eq!(countries, [Some(&b"FR"[..]), None]);
+    /// ```
+    pub fn column(&self, col: impl ColumnKey) -> Option<TableColumn<'_>> {
+        Some(TableColumn { rows: self.iter(), col: col.index_in(self)? })
+    }
+
+    /// Take the first row out as the header. An empty table gets an empty
+    /// header.
+    pub fn with_header(mut self) -> Self {
+        let header = if self.is_empty() { Vec::new() } else { self.remove_row(0) };
+        self.header = Some(header);
+        self
+    }
+
+    /// The header names, if the table has a header.
+    pub fn header(&self) -> Option<&[Vec<u8>]> {
+        self.header.as_deref()
+    }
+
+    /// Replace the header, or drop it with `None`.
+    pub fn set_header<C: AsRef<[u8]>>(&mut self, names: Option<&[C]>) {
+        self.header = names.map(|names| names.iter().map(|name| name.as_ref().to_vec()).collect());
+    }
+
+    /// Index of the first header column named `name`.
+    pub fn column_index(&self, name: &[u8]) -> Option<usize> {
+        self.header.as_ref()?.iter().position(|n| n == name)
+    }
+
+    /// Append a row.
+    pub fn push_row<C: AsRef<[u8]>>(&mut self, cells: impl IntoIterator<Item = C>) {
+        self.insert_row(self.len(), cells);
+    }
+
+    /// Insert a row before row `index`.
+    ///
+    /// # Panics
+    ///
+    /// If `index > self.len()`.
+    pub fn insert_row<C: AsRef<[u8]>>(&mut self, index: usize, cells: impl IntoIterator<Item = C>) {
+        assert!(index <= self.len(), "row index {} out of range for table of {} rows", index, self.len());
+        let first_cell = self.rows[index];
+        let at = self.cells[first_cell];
+
+        let mut bytes = Vec::new();
+        let mut ends = Vec::new();
+        for cell in cells {
+            bytes.extend_from_slice(cell.as_ref());
+            ends.push(at + bytes.len());
+        }
+
+        self.data.splice(at..at, bytes.iter().copied());
+        for end in &mut self.cells[first_cell + 1..] {
+            *end += bytes.len();
+        }
+        self.cells.splice(first_cell + 1..first_cell + 1, ends.iter().copied());
+        for start in &mut self.rows[index + 1..] {
+            *start += ends.len();
+        }
+        self.rows.insert(index + 1, first_cell + ends.len());
+    }
+
+    /// Remove row `index`, returning its cells.
+    ///
+    /// # Panics
+    ///
+    /// If `index >= self.len()`.
+    pub fn remove_row(&mut self, index: usize) -> Vec<Vec<u8>> {
+        assert!(index < self.len(), "row index {} out of range for table of {} rows", index, self.len());
+        let cells = self.rows[index]..self.rows[index + 1];
+        let bytes = self.cells[cells.start]..self.cells[cells.end];
+
+        let removed = self.row_unchecked(index).iter().map(<[u8]>::to_vec).collect();
+        self.data.drain(bytes.clone());
+        self.cells.drain(cells.start + 1..cells.end + 1);
+        for end in &mut self.cells[cells.start + 1..] {
+            *end -= bytes.len();
+        }
+        self.rows.remove(index + 1);
+        for start in &mut self.rows[index + 1..] {
+            *start -= cells.len();
+        }
+        removed
+    }
+
+    /// Insert a column before column `index`, one cell per row from `cells`,
+    /// starting with the header name when the table has a header.
+    ///
+    /// Rows shorter than `index` are padded with empty cells first. If `cells`
+    /// runs out, the remaining rows get empty cells.
+    pub fn insert_column<C: AsRef<[u8]>>(&mut self, index: usize, cells: impl IntoIterator<Item = C>) {
+        let mut cells = cells.into_iter();
+        if let Some(header) = &mut self.header {
+            let name = cells.next().map_or_else(Vec::new, |name| name.as_ref().to_vec());
+            pad_insert(header, index, name);
+        }
+        self.rebuild(|row, rebuilt| {
+            let mut row: Vec<&[u8]> = row.iter().collect();
+            let cell = cells.next();
+            pad_insert(&mut row, index, cell.as_ref().map_or(&b""[..], |cell| cell.as_ref()));
+            rebuilt.push_cells(row);
+        });
+    }
+
+    /// Remove column `col` from the header and every row, returning the
+    /// removed cells of the rows (`None` for rows too short to have it).
+    /// Returns `None`, leaving the table as is, if `col` is a name missing
+    /// from the header.
+    pub fn remove_column(&mut self, col: impl ColumnKey) -> Option<Vec<Option<Vec<u8>>>> {
+        let index = col.index_in(self)?;
+        if let Some(header) = &mut self.header {
+            if index < header.len() {
+                header.remove(index);
+            }
+        }
+        let mut removed = Vec::with_capacity(self.len());
+        self.rebuild(|row, rebuilt| {
+            removed.push(row.get(index).map(<[u8]>::to_vec));
+            rebuilt.push_cells(row.iter().enumerate().filter(|&(col, _)| col != index).map(|(_, cell)| cell));
+        });
+        Some(removed)
+    }
+
+    /// Sort the rows with `compare`, keeping equal rows in order.
+    pub fn sort_by(&mut self, mut compare: impl FnMut(&TableRow<'_>, &TableRow<'_>) -> Ordering) {
+        let mut order: Vec<usize> = (0..self.len()).collect();
+        order.sort_by(|&a, &b| compare(&self.row_unchecked(a), &self.row_unchecked(b)));
+
+        let mut sorted = Table { header: self.header.take(), ..Table::default() };
+        sorted.data.reserve(self.data.len());
+        for row in order {
+            sorted.push_cells(self.row_unchecked(row).iter());
+        }
+        *self = sorted;
+    }
+
+    /// Sort the rows by the bytes of column `col`, keeping equal rows in
+    /// order. Rows too short to have the column come first. Returns `None`,
+    /// leaving the table as is, if `col` is a name missing from the header.
+    pub fn sort_by_column(&mut self, col: impl ColumnKey) -> Option<()> {
+        let index = col.index_in(self)?;
+        self.sort_by(|a, b| a.get(index).cmp(&b.get(index)));
+        Some(())
+    }
+
+    /// Encode back to NSV, header first, through [`crate::encode_bytes`]'s
+    /// encoder: in parallel for large tables.
+    pub fn encode(&self) -> Vec<u8> {
+        let header = self.header.iter().map(|names| names.iter().map(Vec::as_slice).collect());
+        let rows: Vec<Vec<&[u8]>> = header.chain(self.iter().map(|row| row.iter().collect())).collect();
+        crate::encode_rows(&rows)
+    }
+
+    /// Same as [`encode`](Self::encode).
+    pub fn to_nsv(&self) -> Vec<u8> {
+        self.encode()
+    }
+
+    /// Rebuild the arena, with `f` pushing the new version of each row.
+    fn rebuild(&mut self, mut f: impl FnMut(TableRow<'_>, &mut Table)) {
+        let mut rebuilt = Table { header: self.header.take(), ..Table::default() };
+        rebuilt.data.reserve(self.data.len());
+        for row in self.iter() {
+            f(row, &mut rebuilt);
+        }
+        *self = rebuilt;
+    }
+
+    /// Append a row; [`push_row`](Self::push_row) without the splice.
+    fn push_cells<C: AsRef<[u8]>>(&mut self, cells: impl IntoIterator<Item = C>) {
+        for cell in cells {
+            self.data.extend_from_slice(cell.as_ref());
+            self.cells.push(self.data.len());
+        }
+        self.rows.push(self.cells.len() - 1);
+    }
+
+    fn row_unchecked(&self, row: usize) -> TableRow<'_> {
+        TableRow { table: self, cells: self.rows[row]..self.rows[row + 1] }
+    }
+
+    fn cell_unchecked(&self, cell: usize) -> &[u8] {
+        &self.data[self.cells[cell]..self.cells[cell + 1]]
+    }
+}
+
+/// Decode one piece of input, starting at a row start.
+fn decode_piece(piece: &[u8]) -> Table {
+    let mut table = Table { data: Vec::with_capacity(piece.len()), ..Table::default() };
+    let push_cell = |table: &mut Table, raw: &[u8], escaped: bool| {
+        if escaped {
+            crate::unescape_into(raw, &mut table.data);
+        } else {
+            table.data.extend_from_slice(raw);
+        }
+        table.cells.push(table.data.len());
+    };
+    let end_row = |table: &mut Table| table.rows.push(table.cells.len() - 1);
+
+    let (rest, escaped) = crate::scan_lines(piece, |start, end, escaped| {
+        if end > start {
+            push_cell(&mut table, &piece[start..end], escaped);
+        } else {
+            end_row(&mut table);
+        }
+    });
+    if rest < piece.len() {
+        push_cell(&mut table, &piece[rest..], escaped);
+    }
+    // An unterminated last row still counts if it has cells.
+    if table.cells.len() - 1 > table.rows[table.rows.len() - 1] {
+        end_row(&mut table);
+    }
+    table
+}
+
+/// Concatenate the tables of consecutive pieces.
+fn concat(pieces: Vec<Table>) -> Table {
+    if pieces.len() == 1 {
+        return pieces.into_iter().next().unwrap();
+    }
+    let mut table = Table {
+        data: Vec::with_capacity(pieces.iter().map(|piece| piece.data.len()).sum()),
+        cells: Vec::with_capacity(pieces.iter().map(Table::cell_count).sum::<usize>() + 1),
+        rows: Vec::with_capacity(pieces.iter().map(Table::len).sum::<usize>() + 1),
+        header: None,
+    };
+    table.cells.push(0);
+    table.rows.push(0);
+    for piece in pieces {
+        let (data_base, cell_base) = (table.data.len(), table.cell_count());
+        table.data.extend_from_slice(&piece.data);
+        table.cells.extend(piece.cells[1..].iter().map(|&end| data_base + end));
+        table.rows.extend(piece.rows[1..].iter().map(|&end| cell_base + end));
+    }
+    table
+}
+
+/// Insert `value` at `index`, padding `row` with defaults up to it.
+fn pad_insert<T: Default>(row: &mut Vec<T>, index: usize, value: T) {
+    if row.len() < index {
+        row.resize_with(index, T::default);
+    }
+    row.insert(index, value);
+}
+
+/// Rows of cells, as from [`crate::decode`] or [`crate::decode_bytes`]; no
+/// header.
+impl<C: AsRef<[u8]>> From<Vec<Vec<C>>> for Table {
+    fn from(rows: Vec<Vec<C>>) -> Self {
+        Self::from(&rows[..])
+    }
+}
+
+impl<C: AsRef<[u8]>> From<&[Vec<C>]> for Table {
+    fn from(rows: &[Vec<C>]) -> Self {
+        rows.iter().collect()
+    }
+}
+
+impl<R: IntoIterator<Item = C>, C: AsRef<[u8]>> FromIterator<R> for Table {
+    fn from_iter<I: IntoIterator<Item = R>>(rows: I) -> Self {
+        let mut table = Table::default();
+        for row in rows {
+            table.push_cells(row);
+        }
+        table
+    }
+}
+
+/// A column of a [`Table`]: its index, or a header name.
+pub trait ColumnKey {
+    /// The column's index in `table`, or `None` for a name missing from its
+    /// header.
+    fn index_in(&self, table: &Table) -> Option<usize>;
+}
+
+impl ColumnKey for usize {
+    fn index_in(&self, _table: &Table) -> Option<usize> {
+        Some(*self)
+    }
+}
+
+impl ColumnKey for &str {
+    fn index_in(&self, table: &Table) -> Option<usize> {
+        table.column_index(self.as_bytes())
+    }
+}
+
+impl ColumnKey for &[u8] {
+    fn index_in(&self, table: &Table) -> Option<usize> {
+        table.column_index(self)
+    }
+}
+
+impl<const N: usize> ColumnKey for &[u8; N] {
+    fn index_in(&self, table: &Table) -> Option<usize> {
+        table.column_index(&self[..])
+    }
+}
+
+impl<'t> IntoIterator for &'t Table {
+    type Item = TableRow<'t>;
+    type IntoIter = TableRows<'t>;
+
+    fn into_iter(self) -> TableRows<'t> {
+        self.iter()
+    }
+}
+
+/// A row of a [`Table`].
+#[derive(Clone)]
+pub struct TableRow<'t> {
+    table: &'t Table,
+    cells: Range<usize>,
+}
+
+impl<'t> TableRow<'t> {
+    /// Number of cells.
+    pub fn len(&self) -> usize {
+        self.cells.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.cells.is_empty()
+    }
+
+    /// Cell `col`, or `None` past the end of the row.
+    pub fn get(&self, col: usize) -> Option<&'t [u8]> {
+        (col < self.len()).then(|| self.table.cell_unchecked(self.cells.start + col))
+    }
+
+    pub fn iter(&self) -> TableCells<'t> {
+        TableCells { table: self.table, cells: self.cells.clone() }
+    }
+}
+
+impl fmt::Debug for TableRow<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_list().entries(self.iter().map(String::from_utf8_lossy)).finish()
+    }
+}
+
+impl<'t> IntoIterator for TableRow<'t> {
+    type Item = &'t [u8];
+    type IntoIter = TableCells<'t>;
+
+    fn into_iter(self) -> TableCells<'t> {
+        TableCells { table: self.table, cells: self.cells }
+    }
+}
+
+impl<'t> IntoIterator for &TableRow<'t> {
+    type Item = &'t [u8];
+    type IntoIter = TableCells<'t>;
+
+    fn into_iter(self) -> TableCells<'t> {
+        self.iter()
+    }
+}
+
+/// Iterator over the rows of a [`Table`].
+#[derive(Debug, Clone)]
+pub struct TableRows<'t> {
+    table: &'t Table,
+    front: usize,
+    back: usize,
+}
+
+impl<'t> Iterator for TableRows<'t> {
+    type Item = TableRow<'t>;
+
+    fn next(&mut self) -> Option<TableRow<'t>> {
+        if self.front == self.back {
+            return None;
+        }
+        self.front += 1;
+        Some(self.table.row_unchecked(self.front - 1))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.back - self.front;
+        (len, Some(len))
+    }
+}
+
+impl DoubleEndedIterator for TableRows<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        if self.front == self.back {
+            return None;
+        }
+        self.back -= 1;
+        Some(self.table.row_unchecked(self.back))
+    }
+}
+
+impl ExactSizeIterator for TableRows<'_> {}
+impl FusedIterator for TableRows<'_> {}
+
+/// Iterator over one column of a [`Table`]. See [`Table::column`].
+#[derive(Debug, Clone)]
+pub struct TableColumn<'t> {
+    rows: TableRows<'t>,
+    col: usize,
+}
+
+impl<'t> Iterator for TableColumn<'t> {
+    type Item = Option<&'t [u8]>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.rows.next().map(|row| row.get(self.col))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.rows.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for TableColumn<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.rows.next_back().map(|row| row.get(self.col))
+    }
+}
+
+impl ExactSizeIterator for TableColumn<'_> {}
+impl FusedIterator for TableColumn<'_> {}
+
+/// Iterator over the cells of a [`TableRow`].
+#[derive(Debug, Clone)]
+pub struct TableCells<'t> {
+    table: &'t Table,
+    cells: Range<usize>,
+}
+
+impl<'t> Iterator for TableCells<'t> {
+    type Item = &'t [u8];
+
+    fn next(&mut self) -> Option<&'t [u8]> {
+        self.cells.next().map(|cell| self.table.cell_unchecked(cell))
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        self.cells.size_hint()
+    }
+}
+
+impl DoubleEndedIterator for TableCells<'_> {
+    fn next_back(&mut self) -> Option<Self::Item> {
+        self.cells.next_back().map(|cell| self.table.cell_unchecked(cell))
+    }
+}
+
+impl ExactSizeIterator for TableCells<'_> {}
+impl FusedIterator for TableCells<'_> {}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rows(table: &Table) -> Vec<Vec<Vec<u8>>> {
+        table.iter().map(|row| row.iter().map(<[u8]>::to_vec).collect()).collect()
+    }
+
+    fn decoded(input: &[u8]) -> Vec<Vec<Vec<u8>>> {
+        crate::decode_bytes(input).into_iter().map(|row| row.into_iter().map(|c| c.into_owned()).collect()).collect()
+    }
+
+    #[test]
+    fn test_table_matches_decode_bytes() {
+        for input in [
+            &b""[..],
+            b"\n",
+            b"\n\n\n",
+            b"a\nb\n\nc\n",
+            b"a\\\\\n\\\nb\\nc\n\n\nd\n\\",
+            b"x\\q\ny\\\n\n",
+            b"no\nterminator",
+        ] {
+            let table = Table::decode(input);
+            let expected = decoded(input);
+            assert_eq!(rows(&table), expected, "{:?}", String::from_utf8_lossy(input));
+            assert_eq!(table.len(), expected.len());
+            assert_eq!(table.cell_count(), expected.iter().map(Vec::len).sum::<usize>());
+            assert_eq!(table.encode(), crate::encode_bytes(&expected));
+        }
+    }
+
+    #[test]
+    fn test_table_access() {
+        let table = Table::decode(b"a\n\\\nb\\\\\n\n\nc\n\n");
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get(0, 1), Some(&b""[..]));
+        assert_eq!(table.get(0, 2), Some(&b"b\\"[..]));
+        assert_eq!(table.get(0, 3), None);
+        assert!(table.row(1).unwrap().is_empty());
+        assert!(table.row(3).is_none());
+
+        let row = table.row(0).unwrap();
+        assert_eq!(row.iter().rev().collect::<Vec<_>>(), [&b"b\\"[..], b"", b"a"]);
+        assert_eq!(table.iter().rev().map(|row| row.len()).collect::<Vec<_>>(), [1, 0, 3]);
+        assert_eq!(format!("{:?}", table), r#"[["a", "", "b\\"], [], ["c"]]"#);
+    }
+
+    fn people() -> Table {
+        Table::decode(b"name\ncountry\nage\n\nBob\nDE\n41\n\nAlice\nFR\n\nChen\nFR\n35\n\n").with_header()
+    }
+
+    #[test]
+    fn test_header_and_lookup() {
+        let table = people();
+        assert_eq!(table.header(), Some(&[b"name".to_vec(), b"country".to_vec(), b"age".to_vec()][..]));
+        assert_eq!(table.len(), 3);
+        assert_eq!(table.get(1, "name"), Some(&b"Alice"[..]));
+        assert_eq!(table.get(1, b"country"), Some(&b"FR"[..]));
+        assert_eq!(table.get(1, "age"), None);
+        assert_eq!(table.get(0, "missing"), None);
+        assert_eq!(table.column_index(b"age"), Some(2));
+
+        let ages: Vec<_> = table.column("age").unwrap().collect();
+        assert_eq!(ages, [Some(&b"41"[..]), None, Some(b"35")]);
+        assert_eq!(table.column(7).unwrap().len(), 3);
+        assert!(table.column("missing").is_none());
+        assert!(Table::decode(b"a\n\n").column("a").is_none());
+
+        assert_eq!(Table::default().with_header().header(), Some(&[][..]));
+        assert_eq!(format!("{:?}", Table::decode(b"h\n\nx\n\n").with_header()), r#"Table { header: ["h"], rows: [["x"]] }"#);
+    }
+
+    #[test]
+    fn test_row_edits() {
+        let mut table = people();
+        table.insert_row(1, ["Dana", "", "29"]);
+        table.push_row(Vec::<&[u8]>::new());
+        table.insert_row(0, [b"\xff\n".to_vec()]);
+        assert_eq!(table.len(), 6);
+        assert_eq!(rows(&table)[..3], [vec![b"\xff\n".to_vec()], vec![b"Bob".to_vec(), b"DE".to_vec(), b"41".to_vec()], vec![b"Dana".to_vec(), b"".to_vec(), b"29".to_vec()]]);
+        assert!(table.row(5).unwrap().is_empty());
+
+        assert_eq!(table.remove_row(2), [b"Dana".to_vec(), b"".to_vec(), b"29".to_vec()]);
+        assert_eq!(table.remove_row(0), [b"\xff\n".to_vec()]);
+        assert!(table.remove_row(3).is_empty());
+        assert_eq!(table, people());
+        assert_eq!(table.cell_count(), 8);
+    }
+
+    #[test]
+    fn test_column_edits() {
+        let mut table = people();
+        table.insert_column(1, ["city", "Berlin", "Lyon"]);
+        assert_eq!(table.header().unwrap()[1], b"city");
+        assert_eq!(table.column("city").unwrap().collect::<Vec<_>>(), [Some(&b"Berlin"[..]), Some(b"Lyon"), Some(b"")]);
+        assert_eq!(table.get(0, "country"), Some(&b"DE"[..]));
+
+        // Past the end of short rows: padded
+        table.insert_column(4, ["note", "x"]);
+        assert_eq!(table.get(1, 3), Some(&b""[..]));
+        assert_eq!(table.get(1, "note"), Some(&b""[..]));
+        assert_eq!(table.get(0, "note"), Some(&b"x"[..]));
+
+        assert_eq!(table.remove_column("city").unwrap(), [Some(b"Berlin".to_vec()), Some(b"Lyon".to_vec()), Some(b"".to_vec())]);
+        assert_eq!(table.remove_column(3).unwrap(), [Some(b"x".to_vec()), Some(b"".to_vec()), Some(b"".to_vec())]);
+        assert_eq!(table.header(), people().header());
+
+        // Unknown names fail like lookups do, without touching the table
+        let before = table.clone();
+        assert_eq!(table.remove_column("nope"), None);
+        assert_eq!(table.sort_by_column(&b"nope"[..]), None);
+        assert_eq!(table, before);
+
+        // Ragged: the short row has nothing to remove
+        let mut table = Table::from(vec![vec!["a", "b"], vec!["c"]]);
+        assert_eq!(table.remove_column(1).unwrap(), [Some(b"b".to_vec()), None]);
+        assert_eq!(rows(&table), [vec![b"a".to_vec()], vec![b"c".to_vec()]]);
+    }
+
+    #[test]
+    fn test_sort() {
+        let mut table = people();
+        assert_eq!(table.sort_by_column("country"), Some(()));
+        assert_eq!(table.column("name").unwrap().flatten().collect::<Vec<_>>(), [&b"Bob"[..], b"Alice", b"Chen"]);
+
+        // Missing cells first, ties kept in order
+        table.sort_by_column("age").unwrap();
+        assert_eq!(table.column(0).unwrap().flatten().collect::<Vec<_>>(), [&b"Alice"[..], b"Chen", b"Bob"]);
+        table.sort_by(|a, b| b.len().cmp(&a.len()));
+        assert_eq!(table.column(0).unwrap().flatten().collect::<Vec<_>>(), [&b"Chen"[..], b"Bob", b"Alice"]);
+        assert_eq!(table.header(), people().header());
+    }
+
+    #[test]
+    fn test_from_rows_and_to_nsv() {
+        let strings = crate::decode("a\nb\\n\n\nc\n\n");
+        let bytes = vec![vec![vec![0xC0, 0xFF], vec![]], vec![b"\\".to_vec()]];
+        for (table, expected) in [
+            (Table::from(strings.clone()), crate::encode(&strings).into_bytes()),
+            (Table::from(&bytes[..]), crate::encode_bytes(&bytes)),
+            (Table::from(crate::decode_bytes(b"x\n\\\n\n")), b"x\n\\\n\n".to_vec()),
+        ] {
+            assert_eq!(table.to_nsv(), expected);
+            assert_eq!(table.encode(), expected);
+            assert_eq!(Table::decode(&expected), table);
+        }
+
+        let mut table = Table::from(bytes.clone());
+        table.set_header(Some(&["raw", "empty"]));
+        assert_eq!(table.to_nsv(), [&b"raw\nempty\n\n"[..], &crate::encode_bytes(&bytes)].concat());
+        assert_eq!(Table::decode(&table.to_nsv()).with_header(), table);
+        table.set_header(None::<&[&str]>);
+        assert_eq!(table, Table::from(bytes));
+    }
+
+    #[test]
+    fn test_table_pieces() {
+        let data: Vec<Vec<String>> = (0..2_000).map(|i| (0..i % 4).map(|j| format!("{}\\{}\n", i, j)).collect()).collect();
+        let encoded = crate::encode(&data);
+        let expected = decoded(encoded.as_bytes());
+        for n in [1, 2, 7, 100] {
+            let points = crate::boundaries::split_points(encoded.as_bytes(), n);
+            let pieces = points.windows(2).map(|w| decode_piece(&encoded.as_bytes()[w[0]..w[1]])).collect();
+            let table = concat(pieces);
+            assert_eq!(rows(&table), expected, "{} pieces", n);
+            assert_eq!(table, Table::decode(encoded.as_bytes()));
+        }
+        assert_eq!(concat(Vec::new()), Table::default());
+    }
+
+    #[test]
+    fn test_table_decode_large() {
+        let data: Vec<Vec<String>> = (0..60_000).map(|i| vec![format!("r{}", i), format!("a\nb\\{}", i), String::new()]).collect();
+        let encoded = crate::encode(&data);
+        assert!(encoded.len() > crate::PARALLEL_THRESHOLD);
+
+        let expected = decoded(encoded.as_bytes());
+        assert_eq!(rows(&Table::decode(encoded.as_bytes())), expected);
+        let sequential = DecodeOptions::new().sequential(true);
+        assert_eq!(rows(&Table::decode_with(encoded.as_bytes(), &sequential).unwrap()), expected);
+
+        #[cfg(feature = "parallel")]
+        {
+            let pool = std::sync::Arc::new(rayon::ThreadPoolBuilder::new().num_threads(4).build().unwrap());
+            let table = Table::decode_with(encoded.as_bytes(), &DecodeOptions::new().thread_pool(pool)).unwrap();
+            assert_eq!(rows(&table), expected);
+            assert_eq!(table.encode(), encoded.as_bytes());
+        }
+
+        let token = crate::CancelToken::new();
+        token.cancel();
+        assert_eq!(Table::decode_with(encoded.as_bytes(), &DecodeOptions::new().cancel_token(token)), Err(Cancelled));
+    }
+}
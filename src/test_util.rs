@@ -0,0 +1,19 @@
+//! Fixtures shared by the tests of several modules.
+
+/// Deterministic pseudo-random NSV-ish input, dense in LF and backslash.
+pub(crate) fn random_input(seed: u64, len: usize) -> Vec<u8> {
+    let mut state = seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1;
+    (0..len)
+        .map(|_| {
+            state ^= state << 13;
+            state ^= state >> 7;
+            state ^= state << 17;
+            match state % 10 {
+                0..=2 => b'\n',
+                3 => b'\\',
+                4 => b'n',
+                _ => b'a' + (state % 7) as u8,
+            }
+        })
+        .collect()
+}